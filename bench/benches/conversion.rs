@@ -0,0 +1,50 @@
+use cosmogony::{Zone, ZoneIndex, ZoneType};
+use cosmogony2cities::AdministrativeRegion;
+use criterion::{criterion_group, criterion_main, Criterion};
+use geo_types::{MultiPolygon, Polygon};
+
+/// cities with an INSEE code, postcodes and a boundary of 100 points
+fn synthetic_zones(nb: usize) -> Vec<Zone> {
+    (0..nb)
+        .map(|i| {
+            let mut zone = Zone::default();
+            zone.id = ZoneIndex { index: i };
+            zone.osm_id = format!("relation:{}", i);
+            zone.name = format!("city {}", i);
+            zone.zone_type = Some(ZoneType::City);
+            zone.tags = vec![
+                ("ref:INSEE".to_owned(), format!("{:05}", i % 100_000)),
+                ("addr:postcode".to_owned(), "75011;75012;75020".to_owned()),
+            ]
+            .into_iter()
+            .collect();
+            zone.wikidata = Some(format!("Q{}", i));
+            let (x, y) = ((i % 360) as f64 - 180., (i % 170) as f64 - 85.);
+            zone.center = Some((x + 0.5, y + 0.5).into());
+            let ring: Vec<_> = (0..100)
+                .map(|j| {
+                    let angle = f64::from(j) / 99. * 2. * std::f64::consts::PI;
+                    (x + 0.5 + 0.4 * angle.cos(), y + 0.5 + 0.4 * angle.sin())
+                })
+                .collect();
+            zone.boundary = Some(MultiPolygon(vec![Polygon::new(ring.into(), vec![])]));
+            zone
+        })
+        .collect()
+}
+
+fn convert_zones(c: &mut Criterion) {
+    let zones = synthetic_zones(10_000);
+    c.bench_function("convert 10k zones", move |b| {
+        b.iter(|| {
+            zones
+                .iter()
+                .cloned()
+                .map(|z| AdministrativeRegion::from(z).into_sql_params())
+                .count()
+        })
+    });
+}
+
+criterion_group!(benches, convert_zones);
+criterion_main!(benches);
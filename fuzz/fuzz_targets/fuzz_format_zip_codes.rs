@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|zip_codes: Vec<String>| {
+    if let Some(formatted) = cosmogony2cities::format_zip_codes(&zip_codes) {
+        assert!(!formatted.is_empty());
+    }
+});
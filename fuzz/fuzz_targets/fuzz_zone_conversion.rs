@@ -0,0 +1,79 @@
+#![no_main]
+use cosmogony::{Zone, ZoneIndex};
+use cosmogony2cities::AdministrativeRegion;
+use geo_types::{LineString, MultiPolygon, Polygon};
+use libfuzzer_sys::{arbitrary::Arbitrary, fuzz_target};
+
+/// the fields of a zone read by the conversion
+#[derive(Arbitrary, Debug)]
+struct FuzzZone {
+    index: usize,
+    osm_id: String,
+    admin_level: Option<u32>,
+    name: String,
+    tags: Vec<(String, String)>,
+    zip_codes: Vec<String>,
+    center: Option<(f64, f64)>,
+    boundary: Option<Vec<Vec<(f64, f64)>>>,
+    wikidata: Option<String>,
+}
+
+impl From<FuzzZone> for Zone {
+    fn from(fuzz: FuzzZone) -> Self {
+        let mut zone = Zone::default();
+        zone.id = ZoneIndex { index: fuzz.index };
+        zone.osm_id = fuzz.osm_id;
+        zone.admin_level = fuzz.admin_level;
+        zone.name = fuzz.name;
+        zone.tags = fuzz.tags.into_iter().collect();
+        zone.zip_codes = fuzz.zip_codes;
+        zone.center = fuzz.center.map(Into::into);
+        zone.boundary = fuzz.boundary.map(|rings| {
+            MultiPolygon(
+                rings
+                    .into_iter()
+                    .map(|ring| Polygon::new(LineString::from(ring), vec![]))
+                    .collect(),
+            )
+        });
+        zone.wikidata = fuzz.wikidata;
+        zone
+    }
+}
+
+/// panics if a coordinate of the WKT is not a finite number
+fn assert_finite_wkt(field: &str, wkt: &str) {
+    assert!(
+        !wkt.contains("NaN") && !wkt.contains("inf"),
+        "non-finite {}: {}",
+        field,
+        wkt
+    );
+    let coordinates = wkt
+        .split(|c: char| c == '(' || c == ')' || c == ',' || c == ' ')
+        .filter(|token| !token.is_empty() && !token.chars().all(char::is_alphabetic));
+    for coordinate in coordinates {
+        match coordinate.parse::<f64>() {
+            Ok(c) if c.is_finite() => {}
+            _ => panic!(
+                "invalid coordinate {} in the {}: {}",
+                coordinate, field, wkt
+            ),
+        }
+    }
+}
+
+fuzz_target!(|fuzz: FuzzZone| {
+    let zone = Zone::from(fuzz);
+    // the WKT of the converted cities must never contain a non-finite coordinate
+    if let Ok(admin) = AdministrativeRegion::checked_from(zone.clone()) {
+        let city = serde_json::to_value(&admin).unwrap();
+        for field in &["coord", "computed_centroid", "boundary"] {
+            if let Some(wkt) = city[field].as_str() {
+                assert_finite_wkt(field, wkt);
+            }
+        }
+        let _ = admin.into_sql_params();
+    }
+    let _ = AdministrativeRegion::from(zone);
+});
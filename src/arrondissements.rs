@@ -0,0 +1,184 @@
+use crate::AdministrativeRegion;
+use cosmogony::{Zone, ZoneType};
+use failure::Error;
+use log::{info, warn};
+use postgres::transaction::Transaction;
+use std::str::FromStr;
+
+/// the communes divided in municipal arrondissements: INSEE code of the commune, then the
+/// range of the INSEE codes of its arrondissements
+const MUNICIPALITIES: &[(&str, u32, u32)] = &[
+    // Paris
+    ("75056", 75101, 75120),
+    // Marseille
+    ("13055", 13201, 13216),
+    // Lyon
+    ("69123", 69381, 69389),
+];
+
+/// admin level of the arrondissements linked to their commune
+const ARRONDISSEMENT_LEVEL: i32 = 9;
+
+/// how the arrondissements of Paris, Lyon and Marseille are imported
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArrondissementMode {
+    /// only the commune
+    Merge,
+    /// the commune and its arrondissements, as independent cities
+    Separate,
+    /// the commune and its arrondissements, whose parent_id is the id of the commune
+    ParentLink,
+}
+
+impl FromStr for ArrondissementMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "merge" => Ok(ArrondissementMode::Merge),
+            "separate" => Ok(ArrondissementMode::Separate),
+            "parent-link" => Ok(ArrondissementMode::ParentLink),
+            _ => Err(format!(
+                "invalid arrondissements mode '{}', it should be merge, separate or parent-link",
+                s
+            )),
+        }
+    }
+}
+
+/// INSEE code of the commune of an arrondissement, None if the code is not the one of an
+/// arrondissement
+pub fn commune_insee(insee: &str) -> Option<&'static str> {
+    let code: u32 = insee.parse().ok()?;
+    MUNICIPALITIES
+        .iter()
+        .find(|(_, first, last)| *first <= code && code <= *last)
+        .map(|(commune, _, _)| *commune)
+}
+
+/// true if the zone is a city or a city district with the INSEE code of an arrondissement
+pub fn is_arrondissement(zone: &Zone) -> bool {
+    match zone.zone_type {
+        Some(ZoneType::City) | Some(ZoneType::CityDistrict) => zone
+            .tags
+            .get("ref:INSEE")
+            .map_or(false, |insee| commune_insee(insee).is_some()),
+        _ => false,
+    }
+}
+
+/// true if the zone is imported as a city: the cities that are not arrondissements, and the
+/// arrondissements unless they are merged in their commune
+pub fn is_imported(zone: &Zone, mode: ArrondissementMode) -> bool {
+    if is_arrondissement(zone) {
+        mode != ArrondissementMode::Merge
+    } else {
+        zone.zone_type == Some(ZoneType::City)
+    }
+}
+
+/// set the level of an arrondissement, its parent_id being set once in the database
+pub fn set_level(city: &mut AdministrativeRegion) {
+    if city
+        .insee
+        .as_ref()
+        .map_or(false, |insee| commune_insee(insee).is_some())
+    {
+        city.level = Some(ARRONDISSEMENT_LEVEL);
+    }
+}
+
+/// VALUES of the INSEE code of each arrondissement with the one of its commune
+fn arrondissement_communes() -> String {
+    MUNICIPALITIES
+        .iter()
+        .flat_map(|(commune, first, last)| {
+            (*first..=*last).map(move |code| format!("('{}', '{}')", code, commune))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// set the parent_id of the arrondissements of the table to the id of their commune, from
+/// its INSEE code
+pub fn link_to_communes(transaction: &Transaction, table: &str) -> Result<(), Error> {
+    let nb_linked = transaction.execute(
+        &format!(
+            "UPDATE {table} a SET parent_id = c.id
+            FROM (VALUES {communes}) AS m(insee, commune), {table} c
+            WHERE a.insee = m.insee AND c.insee = m.commune;",
+            table = table,
+            communes = arrondissement_communes()
+        ),
+        &[],
+    )?;
+    info!("{} arrondissements linked to their commune", nb_linked);
+    let orphans = transaction.query(
+        &format!(
+            "SELECT a.name, a.uri, m.commune FROM {table} a
+            JOIN (VALUES {communes}) AS m(insee, commune) ON a.insee = m.insee
+            WHERE a.parent_id IS NULL;",
+            table = table,
+            communes = arrondissement_communes()
+        ),
+        &[],
+    )?;
+    for row in orphans.iter() {
+        let (name, uri, commune): (String, String, String) = (row.get(0), row.get(1), row.get(2));
+        warn!(
+            "commune {} of the arrondissement {} ({}) not found",
+            commune, name, uri
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn zone(zone_type: ZoneType, insee: &str) -> Zone {
+        let mut zone = Zone::default();
+        zone.zone_type = Some(zone_type);
+        zone.tags = vec![("ref:INSEE".to_owned(), insee.to_owned())]
+            .into_iter()
+            .collect();
+        zone
+    }
+
+    #[test]
+    fn parse_modes() {
+        assert_eq!("merge".parse(), Ok(ArrondissementMode::Merge));
+        assert_eq!("parent-link".parse(), Ok(ArrondissementMode::ParentLink));
+        assert!("link".parse::<ArrondissementMode>().is_err());
+    }
+
+    #[test]
+    fn arrondissement_values() {
+        let values = arrondissement_communes();
+        assert!(values.starts_with("('75101', '75056'), ('75102', '75056')"));
+        assert!(values.ends_with("('69389', '69123')"));
+        assert_eq!(values.matches('(').count(), 20 + 16 + 9);
+    }
+
+    #[test]
+    fn arrondissement_detection() {
+        assert_eq!(commune_insee("75101"), Some("75056"));
+        assert_eq!(commune_insee("75120"), Some("75056"));
+        assert_eq!(commune_insee("13216"), Some("13055"));
+        assert_eq!(commune_insee("69385"), Some("69123"));
+        assert_eq!(commune_insee("75121"), None);
+        assert_eq!(commune_insee("75056"), None);
+        assert_eq!(commune_insee("2A004"), None);
+
+        assert!(is_arrondissement(&zone(ZoneType::CityDistrict, "75104")));
+        assert!(is_arrondissement(&zone(ZoneType::City, "69381")));
+        assert!(!is_arrondissement(&zone(ZoneType::Suburb, "75104")));
+        // the districts of the other cities are not imported
+        assert!(!is_arrondissement(&zone(ZoneType::CityDistrict, "31555")));
+        assert!(!is_imported(
+            &zone(ZoneType::CityDistrict, "31555"),
+            ArrondissementMode::Separate
+        ));
+    }
+}
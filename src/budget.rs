@@ -0,0 +1,181 @@
+use failure::{Error, Fail};
+use log::info;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// why a zone has not been imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// the zone could not be read from the cosmogony file
+    ReadError,
+    /// the name of the city is empty or a placeholder
+    InvalidName,
+    /// the boundary or the center of the city is invalid
+    InvalidGeometry,
+    /// the INSEE code of the city is malformed
+    InvalidInsee,
+    /// the uri of the city cannot be built, see --null-uri-strategy
+    InvalidUri,
+    /// the zone is not a city, never counted in the error budget
+    NotACity,
+}
+
+#[derive(Debug, Fail)]
+#[fail(
+    display = "too many errors, {} zones skipped ({}) but at most {} are tolerated",
+    total, details, limit
+)]
+pub struct TooManyErrors {
+    total: usize,
+    details: String,
+    limit: usize,
+}
+
+/// number of zones that can be skipped, whatever the reason, before aborting the import
+#[derive(Default)]
+pub struct ErrorBudget {
+    limit: Option<usize>,
+    skipped: Mutex<BTreeMap<SkipReason, usize>>,
+    aborted: Mutex<Option<String>>,
+    /// number of non-fatal problems by kind, only reported in the summary
+    warnings: Mutex<BTreeMap<&'static str, usize>>,
+}
+
+impl ErrorBudget {
+    /// a budget of `limit` errors, unlimited if None
+    pub fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            skipped: Mutex::new(BTreeMap::new()),
+            aborted: Mutex::new(None),
+            warnings: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// forget the skipped zones and the warnings, the zones being read again by a retry of
+    /// the import
+    pub fn reset(&self) {
+        if let Ok(mut skipped) = self.skipped.lock() {
+            skipped.clear();
+        }
+        if let Ok(mut aborted) = self.aborted.lock() {
+            aborted.take();
+        }
+        if let Ok(mut warnings) = self.warnings.lock() {
+            warnings.clear();
+        }
+    }
+
+    pub fn skip(&self, reason: SkipReason) {
+        if let Ok(mut skipped) = self.skipped.lock() {
+            *skipped.entry(reason).or_insert(0) += 1;
+        }
+    }
+
+    /// count a problem that did not prevent the import of a zone
+    pub fn warn(&self, kind: &'static str) {
+        if let Ok(mut warnings) = self.warnings.lock() {
+            *warnings.entry(kind).or_insert(0) += 1;
+        }
+    }
+
+    /// abort the import whatever the number of skipped zones
+    pub fn abort(&self, reason: String) {
+        if let Ok(mut aborted) = self.aborted.lock() {
+            aborted.get_or_insert(reason);
+        }
+    }
+
+    fn aborted(&self) -> Option<String> {
+        self.aborted.lock().ok().and_then(|aborted| aborted.clone())
+    }
+
+    pub fn skipped(&self) -> BTreeMap<SkipReason, usize> {
+        self.skipped
+            .lock()
+            .map(|skipped| skipped.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn total(&self) -> usize {
+        self.skipped().values().sum()
+    }
+
+    pub fn exceeded(&self) -> bool {
+        self.aborted().is_some() || self.limit.map_or(false, |limit| self.total() > limit)
+    }
+
+    fn details(&self) -> String {
+        self.skipped()
+            .iter()
+            .map(|(reason, nb)| format!("{:?}: {}", reason, nb))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// fail if more zones than tolerated have been skipped
+    pub fn check(&self) -> Result<(), Error> {
+        if let Some(reason) = self.aborted() {
+            return Err(failure::err_msg(reason));
+        }
+        match self.limit {
+            Some(limit) if self.exceeded() => Err(TooManyErrors {
+                total: self.total(),
+                details: self.details(),
+                limit,
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn log_summary(&self) {
+        if self.total() > 0 {
+            info!("{} zones skipped ({})", self.total(), self.details());
+        }
+        if let Ok(warnings) = self.warnings.lock() {
+            for (kind, nb) in warnings.iter() {
+                info!("{} zones imported with a warning {}", nb, kind);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn unlimited_budget() {
+        let budget = ErrorBudget::default();
+        budget.skip(SkipReason::ReadError);
+        budget.skip(SkipReason::ReadError);
+        assert_eq!(budget.total(), 2);
+        assert!(!budget.exceeded());
+        assert!(budget.check().is_ok());
+    }
+
+    #[test]
+    fn exceeded_budget() {
+        let budget = ErrorBudget::new(Some(1));
+        budget.skip(SkipReason::ReadError);
+        assert!(budget.check().is_ok());
+        budget.skip(SkipReason::ReadError);
+        assert!(budget.exceeded());
+        assert_eq!(
+            budget.check().unwrap_err().to_string(),
+            "too many errors, 2 zones skipped (ReadError: 2) but at most 1 are tolerated"
+        );
+    }
+
+    #[test]
+    fn aborted_budget() {
+        let budget = ErrorBudget::default();
+        budget.abort("first".to_string());
+        budget.abort("second".to_string());
+        assert!(budget.exceeded());
+        assert_eq!(budget.check().unwrap_err().to_string(), "first");
+    }
+}
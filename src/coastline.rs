@@ -0,0 +1,133 @@
+use failure::{bail, format_err, Error};
+use geo::algorithm::haversine_distance::HaversineDistance;
+use geo::algorithm::vincenty_distance::VincentyDistance;
+use geo_types::Point;
+use geojson::{GeoJson, Value};
+use log::info;
+use rstar::RTree;
+
+/// the point on the unit sphere. The chord between 2 of them grows with their great-circle
+/// distance, so the nearest neighbour in the RTree is also the nearest point on earth
+fn unit_vector(point: &Point<f64>) -> [f64; 3] {
+    let (lon, lat) = (point.x().to_radians(), point.y().to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+fn from_unit_vector(v: &[f64; 3]) -> Point<f64> {
+    Point::new(
+        v[1].atan2(v[0]).to_degrees(),
+        v[2].max(-1.).min(1.).asin().to_degrees(),
+    )
+}
+
+/// the points of the coastline given by --coastal-coastline-file, indexed for the
+/// nearest neighbour queries
+pub struct Coastline {
+    points: RTree<[f64; 3]>,
+}
+
+impl Coastline {
+    /// the coastline of a GeoJSON file, a LineString or a MultiLineString, alone or in
+    /// features
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format_err!("impossible to read coastline file {}: {}", path, e))?;
+        let coastline = Self::from_geojson(&content)
+            .map_err(|e| format_err!("invalid coastline file {}: {}", path, e))?;
+        info!(
+            "{} points of coastline loaded from {}",
+            coastline.points.size(),
+            path
+        );
+        Ok(coastline)
+    }
+
+    fn from_geojson(content: &str) -> Result<Self, Error> {
+        let geometries = match content
+            .parse::<GeoJson>()
+            .map_err(|e| format_err!("{}", e))?
+        {
+            GeoJson::Geometry(geometry) => vec![geometry],
+            GeoJson::Feature(feature) => feature.geometry.into_iter().collect(),
+            GeoJson::FeatureCollection(collection) => collection
+                .features
+                .into_iter()
+                .filter_map(|feature| feature.geometry)
+                .collect(),
+        };
+        let mut points = vec![];
+        for geometry in geometries {
+            let lines = match geometry.value {
+                Value::LineString(line) => vec![line],
+                Value::MultiLineString(lines) => lines,
+                _ => bail!("the coastline should be made of LineStrings or MultiLineStrings"),
+            };
+            for position in lines.iter().flatten() {
+                if position.len() < 2 || !position[0].is_finite() || !position[1].is_finite() {
+                    bail!("invalid position {:?}", position);
+                }
+                points.push(unit_vector(&Point::new(position[0], position[1])));
+            }
+        }
+        if points.is_empty() {
+            bail!("the coastline has no point");
+        }
+        Ok(Self {
+            points: RTree::bulk_load(points),
+        })
+    }
+
+    /// geodesic distance in km from the point to the nearest point of the coastline, with
+    /// Vincenty's formula, or the haversine one when it does not converge
+    pub fn distance_km(&self, point: &Point<f64>) -> Option<f64> {
+        let nearest = from_unit_vector(self.points.nearest_neighbor(&unit_vector(point))?);
+        let meters = point
+            .vincenty_distance(&nearest)
+            .unwrap_or_else(|_| point.haversine_distance(&nearest));
+        Some(meters / 1000.)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const COASTLINE: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "properties": {},
+                "geometry": {"type": "LineString", "coordinates": [[-5, 48], [0, 48], [5, 48]]}
+            },
+            {
+                "type": "Feature",
+                "properties": {},
+                "geometry": {"type": "MultiLineString", "coordinates": [[[170, 0], [179.9, 0]]]}
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn nearest_coastline_point() {
+        let coastline = Coastline::from_geojson(COASTLINE).unwrap();
+        assert_eq!(coastline.points.size(), 5);
+
+        // one degree of latitude north of the second point
+        let distance = coastline.distance_km(&Point::new(0., 49.)).unwrap();
+        assert!((distance - 111.2).abs() < 0.5, "{}", distance);
+        // the nearest point is found across the antimeridian
+        let distance = coastline.distance_km(&Point::new(-179.9, 0.)).unwrap();
+        assert!((distance - 22.3).abs() < 0.5, "{}", distance);
+    }
+
+    #[test]
+    fn invalid_coastlines() {
+        let polygon = r#"{"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [0, 1], [0, 0]]]}"#;
+        assert!(Coastline::from_geojson(polygon).is_err());
+        let empty = r#"{"type": "FeatureCollection", "features": []}"#;
+        assert!(Coastline::from_geojson(empty).is_err());
+        let position = r#"{"type": "LineString", "coordinates": [[0], [1, 0]]}"#;
+        assert!(Coastline::from_geojson(position).is_err());
+    }
+}
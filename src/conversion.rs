@@ -0,0 +1,698 @@
+use crate::budget::SkipReason;
+use crate::{names, national_code, AdministrativeRegion, DEFAULT_CODE_TAG};
+use cosmogony::Zone;
+use failure::Fail;
+use geo_types::{LineString, MultiPolygon, Polygon};
+use rstar::{RTree, RTreeObject, AABB};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+/// why a zone cannot be converted to a city
+#[derive(Debug, Fail, PartialEq)]
+pub enum ConversionError {
+    #[fail(display = "invalid geometry: {}", _0)]
+    InvalidGeometry(String),
+    #[fail(display = "invalid INSEE code '{}'", _0)]
+    InvalidInsee(String),
+    #[fail(display = "empty or placeholder name '{}'", _0)]
+    InvalidName(String),
+}
+
+impl ConversionError {
+    pub fn skip_reason(&self) -> SkipReason {
+        match self {
+            ConversionError::InvalidGeometry(_) => SkipReason::InvalidGeometry,
+            ConversionError::InvalidInsee(_) => SkipReason::InvalidInsee,
+            ConversionError::InvalidName(_) => SkipReason::InvalidName,
+        }
+    }
+}
+
+/// a problem found in a zone, not preventing its conversion
+#[derive(Debug, Clone, Fail, PartialEq)]
+pub enum ConversionWarning {
+    #[fail(display = "invalid geometry: {}", _0)]
+    InvalidGeometry(String),
+    #[fail(display = "invalid INSEE code '{}'", _0)]
+    InvalidInsee(String),
+    #[fail(display = "invalid postcode '{}'", _0)]
+    InvalidPostcode(String),
+    #[fail(
+        display = "addr:postcode '{}' and postal_code '{}' disagree, using {}",
+        addr, postal, used
+    )]
+    PostcodeConflict {
+        addr: String,
+        postal: String,
+        used: &'static str,
+    },
+}
+
+impl ConversionWarning {
+    /// name of the kind of warning, for the summary
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ConversionWarning::InvalidGeometry(_) => "invalid_geometry",
+            ConversionWarning::InvalidInsee(_) => "invalid_insee",
+            ConversionWarning::InvalidPostcode(_) => "invalid_postcode",
+            ConversionWarning::PostcodeConflict { .. } => "postcode_conflict",
+        }
+    }
+}
+
+/// the postcode tags giving the zip codes of a zone
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PostcodePriority {
+    /// addr:postcode, or postal_code when the zone has no addr:postcode
+    Addr,
+    /// postal_code, or addr:postcode when the zone has no postal_code
+    Postal,
+    /// the codes of both tags
+    Union,
+}
+
+impl Default for PostcodePriority {
+    fn default() -> Self {
+        PostcodePriority::Addr
+    }
+}
+
+impl FromStr for PostcodePriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "addr" => Ok(PostcodePriority::Addr),
+            "postal" => Ok(PostcodePriority::Postal),
+            "union" => Ok(PostcodePriority::Union),
+            _ => Err(format!(
+                "invalid postcode priority '{}', it should be addr, postal or union",
+                s
+            )),
+        }
+    }
+}
+
+impl PostcodePriority {
+    /// the tags whose codes are used when the zone has both
+    fn used_tags(self) -> &'static str {
+        match self {
+            PostcodePriority::Addr => "addr:postcode",
+            PostcodePriority::Postal => "postal_code",
+            PostcodePriority::Union => "both",
+        }
+    }
+}
+
+fn split_postcodes(value: &str) -> impl Iterator<Item = &str> {
+    value.split(';').filter(|s| !s.is_empty())
+}
+
+/// the postcodes of the zone, from its addr:postcode and postal_code tags according to the
+/// priority
+pub fn postcodes(zone: &Zone, priority: PostcodePriority) -> Vec<&str> {
+    let addr = zone.tags.get("addr:postcode").map(String::as_str);
+    let postal = zone.tags.get("postal_code").map(String::as_str);
+    let value = match (priority, addr, postal) {
+        (PostcodePriority::Union, Some(addr), Some(postal)) => {
+            let mut codes: Vec<_> = split_postcodes(addr).collect();
+            for code in split_postcodes(postal) {
+                if !codes.iter().any(|c| c.trim() == code.trim()) {
+                    codes.push(code);
+                }
+            }
+            return codes;
+        }
+        (PostcodePriority::Postal, _, Some(postal)) => postal,
+        _ => addr.or(postal).unwrap_or(""),
+    };
+    split_postcodes(value).collect()
+}
+
+fn postcode_set(value: &str) -> BTreeSet<&str> {
+    split_postcodes(value).map(str::trim).collect()
+}
+
+/// a warning if the zone has both postcode tags with different codes
+fn postcode_conflict(zone: &Zone, priority: PostcodePriority) -> Option<ConversionWarning> {
+    let addr = zone.tags.get("addr:postcode")?;
+    let postal = zone.tags.get("postal_code")?;
+    if postcode_set(addr) == postcode_set(postal) {
+        return None;
+    }
+    Some(ConversionWarning::PostcodeConflict {
+        addr: addr.clone(),
+        postal: postal.clone(),
+        used: priority.used_tags(),
+    })
+}
+
+/// 5 characters: the département (2 digits, or 2A/2B for Corsica) then 3 digits
+fn is_valid_insee(insee: &str) -> bool {
+    let bytes = insee.as_bytes();
+    bytes.len() == 5
+        && bytes[2..].iter().all(u8::is_ascii_digit)
+        && (bytes[..2].iter().all(u8::is_ascii_digit)
+            || &bytes[..2] == b"2A"
+            || &bytes[..2] == b"2B")
+}
+
+fn is_valid_postcode(postcode: &str) -> bool {
+    postcode.chars().any(|c| c.is_ascii_digit())
+        && postcode
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-')
+}
+
+fn is_valid_ring(ring: &LineString<f64>) -> bool {
+    ring.0.len() >= 4
+        && ring.0.first() == ring.0.last()
+        && ring.0.iter().all(|c| c.x.is_finite() && c.y.is_finite())
+}
+
+/// twice the area of the ring, positive if counterclockwise
+fn signed_area(ring: &LineString<f64>) -> f64 {
+    ring.0
+        .windows(2)
+        .map(|w| w[0].x * w[1].y - w[1].x * w[0].y)
+        .sum()
+}
+
+/// true if all the rings of the boundary are closed, have at least 4 points and an area.
+/// Self-intersections are not checked, see has_self_intersection
+pub fn is_valid_boundary(boundary: &MultiPolygon<f64>) -> bool {
+    boundary
+        .0
+        .iter()
+        .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors()))
+        .all(|ring| is_valid_ring(ring) && signed_area(ring) != 0.)
+}
+
+/// a segment of a ring of a polygon, indexed to find the segments it crosses
+struct Segment {
+    ring: usize,
+    index: usize,
+    start: [f64; 2],
+    end: [f64; 2],
+}
+
+impl RTreeObject for Segment {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_corners(self.start, self.end)
+    }
+}
+
+/// positive if c is on the left of the line from a to b, 0 if the 3 points are aligned
+fn orientation(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// true if the point, aligned with the segment, is on it
+fn is_on_segment(p: [f64; 2], s: &Segment) -> bool {
+    p[0] >= s.start[0].min(s.end[0])
+        && p[0] <= s.start[0].max(s.end[0])
+        && p[1] >= s.start[1].min(s.end[1])
+        && p[1] <= s.start[1].max(s.end[1])
+}
+
+/// true if the segments cross each other, or only with `touching`, if they have a common point
+fn segments_intersect(s: &Segment, t: &Segment, touching: bool) -> bool {
+    let d1 = orientation(t.start, t.end, s.start);
+    let d2 = orientation(t.start, t.end, s.end);
+    let d3 = orientation(s.start, s.end, t.start);
+    let d4 = orientation(s.start, s.end, t.end);
+    if d1 * d2 < 0. && d3 * d4 < 0. {
+        return true;
+    }
+    touching
+        && ((d1 == 0. && is_on_segment(s.start, t))
+            || (d2 == 0. && is_on_segment(s.end, t))
+            || (d3 == 0. && is_on_segment(t.start, s))
+            || (d4 == 0. && is_on_segment(t.end, s)))
+}
+
+fn polygon_self_intersects(polygon: &Polygon<f64>) -> bool {
+    let mut segments = vec![];
+    let mut nb_segments = vec![];
+    let rings = std::iter::once(polygon.exterior()).chain(polygon.interiors());
+    for (ring, line) in rings.enumerate() {
+        let mut points: Vec<[f64; 2]> = line.0.iter().map(|c| [c.x, c.y]).collect();
+        // the repeated points would make segments without length
+        points.dedup();
+        nb_segments.push(points.len().saturating_sub(1));
+        segments.extend(points.windows(2).enumerate().map(|(index, w)| Segment {
+            ring,
+            index,
+            start: w[0],
+            end: w[1],
+        }));
+    }
+    let tree = RTree::bulk_load(segments);
+    tree.iter().any(|s| {
+        tree.locate_in_envelope_intersecting(&s.envelope())
+            .filter(|t| (t.ring, t.index) > (s.ring, s.index))
+            .any(|t| {
+                if t.ring != s.ring {
+                    return segments_intersect(s, t, false);
+                }
+                // the consecutive segments of a ring share a point
+                let consecutive =
+                    t.index == s.index + 1 || (s.index == 0 && t.index == nb_segments[s.ring] - 1);
+                !consecutive && segments_intersect(s, t, true)
+            })
+    })
+}
+
+/// true if a ring of the boundary crosses or touches itself, like a bowtie, or crosses
+/// another ring of its polygon. The segments of each polygon are indexed in an RTree, the
+/// rings of the large cities having thousands of points
+pub fn has_self_intersection(boundary: &MultiPolygon<f64>) -> bool {
+    boundary.0.iter().any(polygon_self_intersects)
+}
+
+/// the center and the rings of the boundary are finite, and the rings are valid. An invalid
+/// ring is only a warning unless `strict`, a non-finite coordinate is always an error
+pub(crate) fn check_geometry(
+    zone: &Zone,
+    strict: bool,
+) -> Result<Vec<ConversionWarning>, ConversionError> {
+    if let Some(center) = zone.center {
+        if !center.x().is_finite() || !center.y().is_finite() {
+            return Err(ConversionError::InvalidGeometry(format!(
+                "invalid center ({}, {})",
+                center.x(),
+                center.y()
+            )));
+        }
+    }
+    let rings = || {
+        zone.boundary
+            .iter()
+            .flat_map(|b| b.0.iter())
+            .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors()))
+    };
+    // checked first, the WKT of the boundary must never contain NaN or inf
+    if let Some(c) = rings()
+        .flat_map(|r| r.0.iter())
+        .find(|c| !c.x.is_finite() || !c.y.is_finite())
+    {
+        return Err(ConversionError::InvalidGeometry(format!(
+            "non-finite coordinate ({}, {}) in the boundary",
+            c.x, c.y
+        )));
+    }
+    let mut warnings = vec![];
+    for ring in rings().filter(|r| !is_valid_ring(r)) {
+        let problem = format!("invalid ring of {} points in the boundary", ring.0.len());
+        if strict {
+            return Err(ConversionError::InvalidGeometry(problem));
+        }
+        warnings.push(ConversionWarning::InvalidGeometry(problem));
+    }
+    Ok(warnings)
+}
+
+/// check that the zone can be converted to a city, the first national code found among the
+/// `code_tags` being checked if it is an INSEE code. An invalid INSEE code or ring is only
+/// a warning unless `strict`. The name is checked last, so that a zone with an invalid name
+/// is otherwise valid
+pub fn check_zone(
+    zone: &Zone,
+    code_tags: &[String],
+    name_denylist: &[String],
+    postcode_priority: PostcodePriority,
+    strict: bool,
+) -> Result<Vec<ConversionWarning>, ConversionError> {
+    let mut warnings = check_geometry(zone, strict)?;
+    if let Some(("ref:INSEE", insee)) = national_code(zone, code_tags) {
+        if !is_valid_insee(insee) {
+            if strict {
+                return Err(ConversionError::InvalidInsee(insee.to_owned()));
+            }
+            warnings.push(ConversionWarning::InvalidInsee(insee.to_owned()));
+        }
+    }
+    if names::is_placeholder(&zone.name, name_denylist) {
+        return Err(ConversionError::InvalidName(zone.name.clone()));
+    }
+
+    Ok(warnings
+        .into_iter()
+        .chain(postcode_conflict(zone, postcode_priority))
+        .chain(
+            postcodes(zone, postcode_priority)
+                .into_iter()
+                .filter(|s| !is_valid_postcode(s))
+                .map(|s| ConversionWarning::InvalidPostcode(s.to_owned())),
+        )
+        .collect())
+}
+
+impl AdministrativeRegion {
+    /// the city of the zone, with the warnings of its conversion, or why it cannot be converted
+    pub fn try_from_zone(
+        zone: Zone,
+        code_tags: &[String],
+        name_denylist: &[String],
+        postcode_priority: PostcodePriority,
+        strict: bool,
+    ) -> Result<Self, ConversionError> {
+        let warnings = check_zone(&zone, code_tags, name_denylist, postcode_priority, strict)?;
+        let mut admin = Self::from_zone(zone, code_tags, postcode_priority);
+        admin.warnings = warnings;
+        Ok(admin)
+    }
+
+    /// strict `try_from_zone` with the default code tag, no denied names and the addr:postcode
+    /// first. Not a `TryFrom`, which would conflict with the one implied by `From<Zone>`
+    pub fn checked_from(zone: Zone) -> Result<Self, ConversionError> {
+        Self::try_from_zone(
+            zone,
+            &[DEFAULT_CODE_TAG.to_owned()],
+            &[],
+            PostcodePriority::default(),
+            true,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn zone(name: &str, tags: Vec<(&str, &str)>) -> Zone {
+        let mut zone = Zone::default();
+        zone.name = name.to_owned();
+        zone.tags = tags
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v.to_owned()))
+            .collect();
+        zone
+    }
+
+    #[test]
+    fn valid_zone() {
+        let admin = AdministrativeRegion::checked_from(zone(
+            "Ajaccio",
+            vec![("ref:INSEE", "2A004"), ("addr:postcode", "20000;20090")],
+        ))
+        .unwrap();
+        assert_eq!(admin.uri, "admin:fr:2A004");
+        assert!(admin.warnings.is_empty());
+    }
+
+    #[test]
+    fn postcode_warnings() {
+        let admin = AdministrativeRegion::checked_from(zone(
+            "Paris",
+            vec![("addr:postcode", "75001;750O2?;CEDEX")],
+        ))
+        .unwrap();
+        assert_eq!(
+            admin.warnings,
+            vec![
+                ConversionWarning::InvalidPostcode("750O2?".to_owned()),
+                ConversionWarning::InvalidPostcode("CEDEX".to_owned())
+            ]
+        );
+        // the zone is still converted as before
+        assert_eq!(admin.post_code, Some("750O2?-CEDEX".to_owned()));
+    }
+
+    #[test]
+    fn parse_postcode_priorities() {
+        assert_eq!("postal".parse(), Ok(PostcodePriority::Postal));
+        assert_eq!("union".parse(), Ok(PostcodePriority::Union));
+        assert!("both".parse::<PostcodePriority>().is_err());
+    }
+
+    #[test]
+    fn agreeing_postcode_tags() {
+        let zone = zone(
+            "Lyon",
+            vec![
+                ("addr:postcode", "69001;69002"),
+                ("postal_code", "69002; 69001"),
+            ],
+        );
+        let admin = AdministrativeRegion::checked_from(zone.clone()).unwrap();
+        assert!(admin.warnings.is_empty());
+        assert_eq!(admin.zip_codes, vec!["69001", "69002"]);
+        assert_eq!(
+            postcodes(&zone, PostcodePriority::Union),
+            vec!["69001", "69002"]
+        );
+    }
+
+    #[test]
+    fn disagreeing_postcode_tags() {
+        let zone = zone(
+            "Sète",
+            vec![("addr:postcode", "34200"), ("postal_code", "34201")],
+        );
+        let convert = |priority| {
+            AdministrativeRegion::try_from_zone(
+                zone.clone(),
+                &[DEFAULT_CODE_TAG.to_owned()],
+                &[],
+                priority,
+            )
+            .unwrap()
+        };
+
+        let admin = convert(PostcodePriority::Addr);
+        assert_eq!(admin.zip_codes, vec!["34200"]);
+        assert_eq!(
+            admin.warnings,
+            vec![ConversionWarning::PostcodeConflict {
+                addr: "34200".to_owned(),
+                postal: "34201".to_owned(),
+                used: "addr:postcode",
+            }]
+        );
+        assert_eq!(admin.warnings[0].kind(), "postcode_conflict");
+        assert_eq!(
+            admin.warnings[0].to_string(),
+            "addr:postcode '34200' and postal_code '34201' disagree, using addr:postcode"
+        );
+
+        let admin = convert(PostcodePriority::Postal);
+        assert_eq!(admin.zip_codes, vec!["34201"]);
+        assert_eq!(admin.post_code, Some("34201".to_owned()));
+    }
+
+    #[test]
+    fn union_of_postcode_tags() {
+        let zone = zone(
+            "Marseille",
+            vec![
+                ("addr:postcode", "13001;13002"),
+                ("postal_code", "13002;13016;CEDEX"),
+            ],
+        );
+        let admin = AdministrativeRegion::try_from_zone(
+            zone,
+            &[DEFAULT_CODE_TAG.to_owned()],
+            &[],
+            PostcodePriority::Union,
+        )
+        .unwrap();
+        assert_eq!(admin.zip_codes, vec!["13001", "13002", "13016", "CEDEX"]);
+        assert_eq!(admin.postal_code_array, admin.zip_codes);
+        assert_eq!(admin.post_code, Some("13001-CEDEX".to_owned()));
+        assert_eq!(
+            admin.warnings.iter().map(|w| w.kind()).collect::<Vec<_>>(),
+            vec!["postcode_conflict", "invalid_postcode"]
+        );
+        match &admin.warnings[0] {
+            ConversionWarning::PostcodeConflict { used, .. } => assert_eq!(*used, "both"),
+            other => panic!("unexpected warning: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn invalid_name() {
+        assert_eq!(
+            AdministrativeRegion::checked_from(zone(" ", vec![])).unwrap_err(),
+            ConversionError::InvalidName(" ".to_owned())
+        );
+        let err = AdministrativeRegion::try_from_zone(
+            zone("N/A", vec![]),
+            &[DEFAULT_CODE_TAG.to_owned()],
+            &["n/a".to_owned()],
+            PostcodePriority::default(),
+            true,
+        )
+        .unwrap_err();
+        assert_eq!(err.skip_reason(), SkipReason::InvalidName);
+    }
+
+    #[test]
+    fn invalid_insee() {
+        for insee in &["7511", "ABCDE", "2C004", "75 11"] {
+            assert_eq!(
+                AdministrativeRegion::checked_from(zone("x", vec![("ref:INSEE", insee)]))
+                    .unwrap_err(),
+                ConversionError::InvalidInsee(insee.to_string())
+            );
+        }
+        // only the INSEE codes are checked
+        let admin = AdministrativeRegion::try_from_zone(
+            zone("Roma", vec![("ref:ISTAT", "058091")]),
+            &["ref:INSEE".to_owned(), "ref:ISTAT".to_owned()],
+            &[],
+            PostcodePriority::default(),
+            true,
+        )
+        .unwrap();
+        assert_eq!(admin.insee, Some("058091".to_owned()));
+    }
+
+    #[test]
+    fn invalid_geometry() {
+        let mut flat = zone("x", vec![]);
+        flat.boundary = Some(MultiPolygon(vec![Polygon::new(
+            vec![(0., 0.), (1., 0.), (0., 0.)].into(),
+            vec![],
+        )]));
+        let err = AdministrativeRegion::checked_from(flat).unwrap_err();
+        assert_eq!(err.skip_reason(), SkipReason::InvalidGeometry);
+
+        let mut nan_center = zone("x", vec![]);
+        nan_center.center = Some((std::f64::NAN, 1.).into());
+        match AdministrativeRegion::checked_from(nan_center) {
+            Err(ConversionError::InvalidGeometry(_)) => {}
+            other => panic!("unexpected conversion: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn lenient_checks() {
+        let lenient = |zone| {
+            AdministrativeRegion::try_from_zone(
+                zone,
+                &[DEFAULT_CODE_TAG.to_owned()],
+                &[],
+                PostcodePriority::default(),
+                false,
+            )
+        };
+        let admin = lenient(zone("x", vec![("ref:INSEE", "ABCDE")])).unwrap();
+        assert_eq!(admin.insee, Some("ABCDE".to_owned()));
+        assert_eq!(
+            admin.warnings,
+            vec![ConversionWarning::InvalidInsee("ABCDE".to_owned())]
+        );
+
+        let mut flat = zone("x", vec![]);
+        flat.boundary = Some(MultiPolygon(vec![Polygon::new(
+            vec![(0., 0.), (1., 0.), (0., 0.)].into(),
+            vec![],
+        )]));
+        let admin = lenient(flat).unwrap();
+        assert_eq!(
+            admin.warnings,
+            vec![ConversionWarning::InvalidGeometry(
+                "invalid ring of 3 points in the boundary".to_owned()
+            )]
+        );
+
+        // the WKT must never contain NaN or inf
+        let mut nan_center = zone("x", vec![]);
+        nan_center.center = Some((std::f64::NAN, 1.).into());
+        assert!(lenient(nan_center).is_err());
+    }
+
+    #[test]
+    fn non_finite_coordinates() {
+        let mut infinite_center = zone("x", vec![]);
+        infinite_center.center = Some((2.35, std::f64::INFINITY).into());
+        assert_eq!(
+            AdministrativeRegion::checked_from(infinite_center).unwrap_err(),
+            ConversionError::InvalidGeometry("invalid center (2.35, inf)".to_owned())
+        );
+
+        let mut nan_vertex = zone("x", vec![]);
+        nan_vertex.boundary = Some(MultiPolygon(vec![Polygon::new(
+            vec![(0., 0.), (1., 0.), (std::f64::NAN, 1.), (0., 0.)].into(),
+            vec![],
+        )]));
+        assert_eq!(
+            AdministrativeRegion::checked_from(nan_vertex).unwrap_err(),
+            ConversionError::InvalidGeometry(
+                "non-finite coordinate (NaN, 1) in the boundary".to_owned()
+            )
+        );
+
+        let mut infinite_hole = zone("x", vec![]);
+        infinite_hole.boundary = Some(MultiPolygon(vec![Polygon::new(
+            vec![(0., 0.), (10., 0.), (10., 10.), (0., 0.)].into(),
+            vec![vec![(1., 1.), (std::f64::NEG_INFINITY, 1.), (2., 2.), (1., 1.)].into()],
+        )]));
+        let err = AdministrativeRegion::checked_from(infinite_hole).unwrap_err();
+        assert_eq!(err.skip_reason(), SkipReason::InvalidGeometry);
+    }
+
+    #[test]
+    fn valid_boundaries() {
+        let boundary =
+            |points: Vec<(f64, f64)>| MultiPolygon(vec![Polygon::new(points.into(), vec![])]);
+        assert!(is_valid_boundary(&boundary(vec![
+            (0., 0.),
+            (1., 0.),
+            (1., 1.),
+            (0., 0.)
+        ])));
+        // a ring without area
+        assert!(!is_valid_boundary(&boundary(vec![
+            (0., 0.),
+            (1., 0.),
+            (2., 0.),
+            (0., 0.)
+        ])));
+        assert!(!is_valid_boundary(&boundary(vec![
+            (0., 0.),
+            (1., 0.),
+            (0., 0.)
+        ])));
+    }
+
+    #[test]
+    fn self_intersections() {
+        let ring = |points: Vec<(f64, f64)>| LineString::from(points);
+        let square = ring(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]);
+        let boundary = |exterior, interiors| MultiPolygon(vec![Polygon::new(exterior, interiors)]);
+        assert!(!has_self_intersection(&boundary(square.clone(), vec![])));
+        // a repeated point is not an intersection
+        assert!(!has_self_intersection(&boundary(
+            ring(vec![(0., 0.), (4., 0.), (4., 0.), (4., 4.), (0., 0.)]),
+            vec![]
+        )));
+        let bowtie = ring(vec![(0., 0.), (4., 4.), (4., 0.), (0., 4.), (0., 0.)]);
+        assert!(has_self_intersection(&boundary(bowtie, vec![])));
+        // a ring touching itself at a point
+        let pinched = ring(vec![
+            (0., 0.),
+            (4., 0.),
+            (2., 2.),
+            (4., 4.),
+            (0., 4.),
+            (2., 2.),
+            (0., 0.),
+        ]);
+        assert!(has_self_intersection(&boundary(pinched, vec![])));
+        // a hole touching the exterior ring is valid, but not a hole crossing it
+        let touching_hole = ring(vec![(0., 1.), (1., 1.), (1., 2.), (0., 1.)]);
+        assert!(!has_self_intersection(&boundary(
+            square.clone(),
+            vec![touching_hole]
+        )));
+        let crossing_hole = ring(vec![(1., 1.), (5., 1.), (5., 2.), (1., 1.)]);
+        assert!(has_self_intersection(&boundary(
+            square,
+            vec![crossing_hole]
+        )));
+    }
+}
@@ -0,0 +1,204 @@
+use crate::generations::{self, Generation};
+use failure::Error;
+use postgres::Connection;
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct LevelCount {
+    pub level: Option<i32>,
+    pub count: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DepartmentCount {
+    pub department: String,
+    pub count: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CityArea {
+    pub id: i64,
+    pub name: String,
+    pub area_km2: f64,
+}
+
+/// the active generation of --versioned
+#[derive(Serialize, Debug, PartialEq)]
+pub struct LastImport {
+    pub table_name: String,
+    pub imported_at: String,
+    pub source_md5: Option<String>,
+    pub nb_cities: i64,
+}
+
+impl From<Generation> for LastImport {
+    fn from(generation: Generation) -> Self {
+        Self {
+            table_name: generation.table_name,
+            imported_at: generation.imported_at,
+            source_md5: generation.source_md5,
+            nb_cities: generation.nb_cities,
+        }
+    }
+}
+
+/// content of administrative_regions, reported by --db-stats. The parts needing a column
+/// missing from the table are None
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct DbStats {
+    pub total: i64,
+    pub by_level: Option<Vec<LevelCount>>,
+    pub with_boundary: Option<i64>,
+    pub without_boundary: Option<i64>,
+    pub with_insee: Option<i64>,
+    pub without_insee: Option<i64>,
+    /// cities with a French INSEE code by département
+    pub by_department: Option<Vec<DepartmentCount>>,
+    pub largest: Option<CityArea>,
+    pub smallest: Option<CityArea>,
+    /// only known for the imports done with --versioned
+    pub last_import: Option<LastImport>,
+}
+
+fn columns(cnx: &Connection) -> Result<HashSet<String>, Error> {
+    let rows = cnx.query(
+        "SELECT column_name::text FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = 'administrative_regions';",
+        &[],
+    )?;
+    Ok(rows.iter().map(|row| row.get(0)).collect())
+}
+
+fn count(cnx: &Connection, filter: &str) -> Result<i64, Error> {
+    let rows = cnx.query(
+        &format!(
+            "SELECT count(*) FROM administrative_regions WHERE {};",
+            filter
+        ),
+        &[],
+    )?;
+    Ok(rows.get(0).get(0))
+}
+
+/// the city with the largest boundary, or with the smallest one for `order` ASC
+fn city_by_area(cnx: &Connection, order: &str) -> Result<Option<CityArea>, Error> {
+    let rows = cnx.query(
+        &format!(
+            "SELECT id, name, ST_Area(boundary) / 1e6 AS area_km2 FROM administrative_regions
+            WHERE boundary IS NOT NULL ORDER BY area_km2 {}, id LIMIT 1;",
+            order
+        ),
+        &[],
+    )?;
+    Ok(rows.iter().next().map(|row| CityArea {
+        id: row.get(0),
+        name: row.get(1),
+        area_km2: row.get(2),
+    }))
+}
+
+fn last_import(cnx: &Connection) -> Result<Option<LastImport>, Error> {
+    let rows = cnx.query(
+        "SELECT to_regclass('administrative_regions_generations') IS NOT NULL;",
+        &[],
+    )?;
+    if !rows.get(0).get::<_, bool>(0) {
+        return Ok(None);
+    }
+    Ok(generations::list_generations(cnx)?
+        .into_iter()
+        .find(|g| g.active)
+        .map(LastImport::from))
+}
+
+pub fn db_stats(cnx: &Connection) -> Result<DbStats, Error> {
+    let columns = columns(cnx)?;
+    let has = |column: &str| columns.contains(column);
+    let mut stats = DbStats {
+        total: count(cnx, "TRUE")?,
+        last_import: last_import(cnx)?,
+        ..Default::default()
+    };
+    if has("level") {
+        let rows = cnx.query(
+            "SELECT level, count(*) FROM administrative_regions GROUP BY level ORDER BY level;",
+            &[],
+        )?;
+        stats.by_level = Some(
+            rows.iter()
+                .map(|row| LevelCount {
+                    level: row.get(0),
+                    count: row.get(1),
+                })
+                .collect(),
+        );
+    }
+    if has("boundary") {
+        stats.with_boundary = Some(count(cnx, "boundary IS NOT NULL")?);
+        stats.without_boundary = Some(count(cnx, "boundary IS NULL")?);
+        stats.largest = city_by_area(cnx, "DESC")?;
+        stats.smallest = city_by_area(cnx, "ASC")?;
+    }
+    if has("insee") {
+        stats.with_insee = Some(count(cnx, "insee IS NOT NULL")?);
+        stats.without_insee = Some(count(cnx, "insee IS NULL")?);
+        // like output::split::department_code
+        let rows = cnx.query(
+            "SELECT CASE WHEN left(insee, 2) IN ('97', '98') THEN left(insee, 3)
+                ELSE left(insee, 2) END AS department, count(*)
+            FROM administrative_regions
+            WHERE uri LIKE 'admin:fr:%' AND length(insee) = 5
+            GROUP BY department ORDER BY department;",
+            &[],
+        )?;
+        stats.by_department = Some(
+            rows.iter()
+                .map(|row| DepartmentCount {
+                    department: row.get(0),
+                    count: row.get(1),
+                })
+                .collect(),
+        );
+    }
+    Ok(stats)
+}
+
+impl DbStats {
+    pub fn print(&self) {
+        println!("{} cities", self.total);
+        for level in self.by_level.iter().flatten() {
+            let name = level.level.map_or("none".to_owned(), |l| l.to_string());
+            println!("  level {}: {}", name, level.count);
+        }
+        if let (Some(with), Some(without)) = (self.with_boundary, self.without_boundary) {
+            println!("{} with a boundary, {} without", with, without);
+        }
+        if let (Some(with), Some(without)) = (self.with_insee, self.without_insee) {
+            println!("{} with an INSEE code, {} without", with, without);
+        }
+        for department in self.by_department.iter().flatten() {
+            println!(
+                "  département {}: {}",
+                department.department, department.count
+            );
+        }
+        for (label, city) in &[("largest", &self.largest), ("smallest", &self.smallest)] {
+            if let Some(city) = city {
+                println!(
+                    "{} boundary: {} ({}), {:.3}km²",
+                    label, city.name, city.id, city.area_km2
+                );
+            }
+        }
+        if let Some(import) = &self.last_import {
+            println!(
+                "last import: {} at {}, {} cities, md5 {}",
+                import.table_name,
+                import.imported_at,
+                import.nb_cities,
+                import.source_md5.as_deref().unwrap_or("unknown")
+            );
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{multipolygon_from_wkt, AdministrativeRegion};
+use failure::{Error, Fail};
+use geo_types::MultiPolygon;
+use log::info;
+use postgres::Connection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Fail)]
+#[fail(display = "the import would change {} cities", _0)]
+pub struct ChangesDetected(pub usize);
+
+/// the columns of a row of administrative_regions compared with the input
+struct ExistingCity {
+    name: String,
+    insee: Option<String>,
+    post_code: Option<String>,
+    level: Option<i32>,
+    /// md5 of the WKB of the boundary
+    boundary_md5: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Renamed {
+    pub uri: String,
+    pub old_name: String,
+    pub new_name: String,
+}
+
+/// what an import would change in administrative_regions, the cities being identified by
+/// their uri. A city can be both renamed and have changed boundary or attributes
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct DiffReport {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub renamed: Vec<Renamed>,
+    pub boundary_changed: Vec<String>,
+    /// insee, post_code or level changed
+    pub attributes_changed: Vec<String>,
+}
+
+impl DiffReport {
+    pub fn has_changes(&self) -> bool {
+        self.nb_changes() > 0
+    }
+
+    /// number of changed cities, each counted once
+    pub fn nb_changes(&self) -> usize {
+        let mut changed: Vec<&str> = self
+            .added
+            .iter()
+            .chain(&self.removed)
+            .chain(&self.boundary_changed)
+            .chain(&self.attributes_changed)
+            .map(String::as_str)
+            .chain(self.renamed.iter().map(|r| r.uri.as_str()))
+            .collect();
+        changed.sort();
+        changed.dedup();
+        changed.len()
+    }
+
+    pub fn log(&self) {
+        info!(
+            "{} new cities, {} renamed, {} with a changed boundary, {} with changed attributes, {} would be deleted",
+            self.added.len(),
+            self.renamed.len(),
+            self.boundary_changed.len(),
+            self.attributes_changed.len(),
+            self.removed.len()
+        );
+        for uri in &self.added {
+            info!("new city: {}", uri);
+        }
+        for renamed in &self.renamed {
+            info!(
+                "renamed city: {} ('{}' -> '{}')",
+                renamed.uri, renamed.old_name, renamed.new_name
+            );
+        }
+        for uri in &self.boundary_changed {
+            info!("boundary changed: {}", uri);
+        }
+        for uri in &self.attributes_changed {
+            info!("attributes changed: {}", uri);
+        }
+        for uri in &self.removed {
+            info!("deleted city: {}", uri);
+        }
+    }
+}
+
+/// the boundary as PostGIS' ST_AsBinary: little endian WKB, without SRID
+fn wkb(boundary: &MultiPolygon<f64>) -> Vec<u8> {
+    let mut wkb = vec![1];
+    wkb.extend_from_slice(&6u32.to_le_bytes());
+    wkb.extend_from_slice(&(boundary.0.len() as u32).to_le_bytes());
+    for polygon in &boundary.0 {
+        wkb.push(1);
+        wkb.extend_from_slice(&3u32.to_le_bytes());
+        wkb.extend_from_slice(&(polygon.interiors().len() as u32 + 1).to_le_bytes());
+        for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors()) {
+            wkb.extend_from_slice(&(ring.0.len() as u32).to_le_bytes());
+            for c in &ring.0 {
+                wkb.extend_from_slice(&c.x.to_le_bytes());
+                wkb.extend_from_slice(&c.y.to_le_bytes());
+            }
+        }
+    }
+    wkb
+}
+
+fn boundary_md5(boundary: &MultiPolygon<f64>) -> String {
+    format!("{:x}", md5::compute(wkb(boundary)))
+}
+
+/// true if the 2 boundaries have the same rings, with points closer than precision degrees
+fn within_precision(a: &MultiPolygon<f64>, b: &MultiPolygon<f64>, precision: f64) -> bool {
+    let rings = |m: &MultiPolygon<f64>| -> Vec<Vec<(f64, f64)>> {
+        m.0.iter()
+            .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors()))
+            .map(|r| r.0.iter().map(|c| (c.x, c.y)).collect())
+            .collect()
+    };
+    let (a, b) = (rings(a), rings(b));
+    a.len() == b.len()
+        && a.iter().zip(&b).all(|(ra, rb)| {
+            ra.len() == rb.len()
+                && ra.iter().zip(rb).all(|(pa, pb)| {
+                    (pa.0 - pb.0).abs() <= precision && (pa.1 - pb.1).abs() <= precision
+                })
+        })
+}
+
+fn existing_cities(cnx: &Connection) -> Result<BTreeMap<String, ExistingCity>, Error> {
+    Ok(cnx
+        .query(
+            "SELECT uri, name, insee, post_code, level, md5(ST_AsBinary(boundary))
+            FROM administrative_regions;",
+            &[],
+        )?
+        .iter()
+        .map(|row| {
+            (
+                row.get(0),
+                ExistingCity {
+                    name: row.get(1),
+                    insee: row.get(2),
+                    post_code: row.get(3),
+                    level: row.get(4),
+                    boundary_md5: row.get(5),
+                },
+            )
+        })
+        .collect())
+}
+
+fn existing_boundary(cnx: &Connection, uri: &str) -> Result<Option<MultiPolygon<f64>>, Error> {
+    let rows = cnx.query(
+        "SELECT ST_AsText(boundary) FROM administrative_regions WHERE uri = $1;",
+        &[&uri],
+    )?;
+    match rows
+        .iter()
+        .next()
+        .and_then(|row| row.get::<_, Option<String>>(0))
+    {
+        Some(wkt) => Ok(Some(multipolygon_from_wkt(&wkt)?)),
+        None => Ok(None),
+    }
+}
+
+/// compare the cities with the content of administrative_regions, without modifying it.
+/// With a precision, the boundaries whose points moved by less than precision degrees
+/// are not considered as changed
+pub fn diff_report(
+    admins: impl Iterator<Item = AdministrativeRegion>,
+    cnx: &Connection,
+    precision: Option<f64>,
+) -> Result<DiffReport, Error> {
+    let mut existing = existing_cities(cnx)?;
+    let mut report = DiffReport::default();
+    for admin in admins {
+        let old = match existing.remove(&admin.uri) {
+            Some(old) => old,
+            None => {
+                report.added.push(admin.uri);
+                continue;
+            }
+        };
+        if old.name != admin.name {
+            report.renamed.push(Renamed {
+                uri: admin.uri.clone(),
+                old_name: old.name,
+                new_name: admin.name.clone(),
+            });
+        }
+        let boundary_changed = match (&admin.boundary, precision) {
+            _ if admin.boundary.as_ref().map(boundary_md5) == old.boundary_md5 => false,
+            (Some(new), Some(precision)) if old.boundary_md5.is_some() => {
+                match existing_boundary(cnx, &admin.uri)? {
+                    Some(old) => !within_precision(&old, new, precision),
+                    None => true,
+                }
+            }
+            _ => true,
+        };
+        if boundary_changed {
+            report.boundary_changed.push(admin.uri.clone());
+        }
+        if (&old.insee, &old.post_code, old.level) != (&admin.insee, &admin.post_code, admin.level)
+        {
+            report.attributes_changed.push(admin.uri);
+        }
+    }
+    report.removed = existing.into_keys().collect();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn square(size: f64) -> MultiPolygon<f64> {
+        MultiPolygon(vec![geo_types::Polygon::new(
+            vec![(0., 0.), (size, 0.), (size, size), (0., 0.)].into(),
+            vec![],
+        )])
+    }
+
+    #[test]
+    fn wkb_boundary() {
+        let wkb = wkb(&square(1.));
+        // byte order, type, nb polygons, byte order, type, nb rings, nb points, 4 points
+        assert_eq!(wkb.len(), 1 + 4 + 4 + 1 + 4 + 4 + 4 + 4 * 16);
+        assert_eq!(&wkb[..9], &[1, 6, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(&wkb[9..18], &[1, 3, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(&wkb[18..22], &[4, 0, 0, 0]);
+        assert_eq!(&wkb[38..46], &1f64.to_le_bytes());
+    }
+
+    #[test]
+    fn boundary_precision() {
+        assert!(within_precision(&square(1.), &square(1.000_000_1), 1e-6));
+        assert!(!within_precision(&square(1.), &square(1.001), 1e-6));
+        assert!(!within_precision(&square(1.), &MultiPolygon(vec![]), 1.));
+    }
+}
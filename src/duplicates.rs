@@ -0,0 +1,315 @@
+use crate::{budget::ErrorBudget, to_wkt, zone_index::LocalZoneIndex, AdministrativeRegion};
+use geo_types::{Coordinate, LineString, MultiPolygon, Polygon};
+use log::{debug, info, warn};
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// which city to keep among the ones sharing the same boundary
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicatePolicy {
+    /// the one with an INSEE code, then the lowest osm id
+    KeepInsee,
+    LowestOsmId,
+    Fail,
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep-insee" => Ok(DuplicatePolicy::KeepInsee),
+            "lowest-osm-id" => Ok(DuplicatePolicy::LowestOsmId),
+            "fail" => Ok(DuplicatePolicy::Fail),
+            _ => Err(format!(
+                "invalid duplicate policy '{}', it should be keep-insee, lowest-osm-id or fail",
+                s
+            )),
+        }
+    }
+}
+
+fn cmp_coords(a: &Coordinate<f64>, b: &Coordinate<f64>) -> Ordering {
+    a.x.partial_cmp(&b.x)
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal))
+}
+
+/// twice the signed area of the ring, positive if counterclockwise
+fn signed_area(points: &[Coordinate<f64>]) -> f64 {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum()
+}
+
+/// the same ring, counterclockwise and starting from its smallest point
+fn canonical_ring(ring: &LineString<f64>) -> LineString<f64> {
+    let mut points = ring.0.clone();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    if signed_area(&points) < 0. {
+        points.reverse();
+    }
+    let start = points
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| cmp_coords(a, b))
+        .map_or(0, |(i, _)| i);
+    points.rotate_left(start);
+    if let Some(&first) = points.first() {
+        points.push(first);
+    }
+    LineString(points)
+}
+
+fn canonical_polygon_wkt(polygon: &Polygon<f64>) -> String {
+    let mut interiors: Vec<_> = polygon.interiors().iter().map(canonical_ring).collect();
+    interiors.sort_by_key(|ring| to_wkt(ring.clone()));
+    to_wkt(Polygon::new(canonical_ring(polygon.exterior()), interiors))
+}
+
+/// WKT of the polygons of the boundary, normalized so that the order of the polygons,
+/// of their holes, and the starting point and orientation of the rings do not matter
+fn canonical_polygons(boundary: &MultiPolygon<f64>) -> Vec<String> {
+    let mut polygons: Vec<_> = boundary.0.iter().map(canonical_polygon_wkt).collect();
+    polygons.sort();
+    polygons
+}
+
+/// hash of the normalized WKT of the boundary
+pub fn boundary_hash(boundary: &MultiPolygon<f64>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    canonical_polygons(boundary).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// indexes of the cities sharing the same boundary, by group. The cities are gathered by the
+/// hash of their boundary, then only grouped if their normalized boundaries are equal, two
+/// different boundaries possibly having the same hash
+fn duplicate_groups(
+    admins: &[AdministrativeRegion],
+    hash: impl Fn(&MultiPolygon<f64>) -> u64,
+) -> Vec<Vec<usize>> {
+    let mut candidates: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (i, admin) in admins.iter().enumerate() {
+        if let Some(boundary) = &admin.boundary {
+            candidates.entry(hash(boundary)).or_default().push(i);
+        }
+    }
+    candidates
+        .values()
+        .filter(|candidates| candidates.len() > 1)
+        .flat_map(|candidates| {
+            let mut groups: HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+            for &i in candidates {
+                if let Some(boundary) = &admins[i].boundary {
+                    groups
+                        .entry(canonical_polygons(boundary))
+                        .or_default()
+                        .push(i);
+                }
+            }
+            groups
+                .values()
+                .filter(|group| group.len() > 1)
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn osm_id_key(admin: &AdministrativeRegion) -> (bool, Option<i64>) {
+    (admin.osm_relation_id.is_none(), admin.osm_relation_id)
+}
+
+/// remove the cities sharing the same boundary, keeping one of each group according
+/// to the policy. With the fail policy the budget is aborted and no city is returned
+pub fn remove_duplicate_boundaries(
+    admins: Vec<AdministrativeRegion>,
+    policy: DuplicatePolicy,
+    budget: &ErrorBudget,
+) -> Vec<AdministrativeRegion> {
+    let groups = duplicate_groups(&admins, boundary_hash);
+
+    let mut removed: HashSet<LocalZoneIndex> = HashSet::new();
+    for group in &groups {
+        let uris: Vec<_> = group.iter().map(|&i| admins[i].uri.as_str()).collect();
+        warn!("cities with the same boundary: {}", uris.join(", "));
+        let kept = match policy {
+            DuplicatePolicy::Fail => {
+                budget.abort(format!(
+                    "cities with the same boundary: {}",
+                    uris.join(", ")
+                ));
+                return vec![];
+            }
+            DuplicatePolicy::KeepInsee => group
+                .iter()
+                .min_by_key(|&&i| (admins[i].insee.is_none(), osm_id_key(&admins[i]))),
+            DuplicatePolicy::LowestOsmId => group.iter().min_by_key(|&&i| osm_id_key(&admins[i])),
+        };
+        for &i in group.iter().filter(|&i| Some(i) != kept) {
+            debug!("zone {} removed as a duplicate", admins[i].zone_index());
+            removed.insert(admins[i].zone_index());
+        }
+    }
+    if !groups.is_empty() {
+        info!(
+            "{} groups of cities with the same boundary, {} cities removed",
+            groups.len(),
+            removed.len()
+        );
+    }
+
+    admins
+        .into_iter()
+        .filter(|admin| !removed.contains(&admin.zone_index()))
+        .collect()
+}
+
+/// cities already seen by uri, to report the cities with the same uri before the database
+/// rejects them because of a unique constraint
+#[derive(Default)]
+pub struct UriCollisions {
+    seen: HashMap<String, String>,
+}
+
+fn describe(admin: &AdministrativeRegion) -> String {
+    match admin.osm_relation_id {
+        Some(osm_id) => format!("{} (zone {}, relation {})", admin.name, admin.id, osm_id),
+        None => format!("{} (zone {})", admin.name, admin.id),
+    }
+}
+
+impl UriCollisions {
+    /// description of the collision if a city with the same uri has already been seen
+    pub fn check(&mut self, admin: &AdministrativeRegion) -> Option<String> {
+        match self.seen.get(&admin.uri) {
+            Some(previous) => Some(format!(
+                "{} and {} have the same uri {}",
+                previous,
+                describe(admin),
+                admin.uri
+            )),
+            None => {
+                self.seen.insert(admin.uri.clone(), describe(admin));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn square(points: Vec<(f64, f64)>) -> MultiPolygon<f64> {
+        MultiPolygon(vec![Polygon::new(points.into(), vec![])])
+    }
+
+    fn city(
+        osm_id: &str,
+        insee: Option<&str>,
+        boundary: MultiPolygon<f64>,
+    ) -> AdministrativeRegion {
+        let mut zone = cosmogony::Zone::default();
+        zone.name = osm_id.to_owned();
+        zone.osm_id = osm_id.to_owned();
+        zone.id = cosmogony::ZoneIndex {
+            index: osm_id[1..].parse().unwrap(),
+        };
+        if let Some(insee) = insee {
+            zone.tags = vec![("ref:INSEE".to_owned(), insee.to_owned())]
+                .into_iter()
+                .collect();
+        }
+        zone.boundary = Some(boundary);
+        AdministrativeRegion::from(zone)
+    }
+
+    #[test]
+    fn canonical_boundary_hash() {
+        let original = square(vec![(0., 0.), (1., 0.), (1., 1.), (0., 1.), (0., 0.)]);
+        // same ring, starting from another point and clockwise
+        let reordered = square(vec![(1., 1.), (1., 0.), (0., 0.), (0., 1.), (1., 1.)]);
+        let near = square(vec![
+            (0., 0.),
+            (1., 0.),
+            (1., 1.000_001),
+            (0., 1.),
+            (0., 0.),
+        ]);
+
+        assert_eq!(boundary_hash(&original), boundary_hash(&reordered));
+        assert_ne!(boundary_hash(&original), boundary_hash(&near));
+    }
+
+    #[test]
+    fn duplicate_boundaries() {
+        let unit = || square(vec![(0., 0.), (1., 0.), (1., 1.), (0., 1.), (0., 0.)]);
+        let admins = || {
+            vec![
+                city("r1", None, unit()),
+                city("r2", Some("75056"), unit()),
+                city(
+                    "r3",
+                    None,
+                    square(vec![(2., 2.), (3., 2.), (3., 3.), (2., 2.)]),
+                ),
+            ]
+        };
+        let osm_ids = |admins: Vec<AdministrativeRegion>| -> Vec<Option<i64>> {
+            admins.into_iter().map(|a| a.osm_relation_id).collect()
+        };
+
+        let budget = ErrorBudget::default();
+        let kept = remove_duplicate_boundaries(admins(), DuplicatePolicy::KeepInsee, &budget);
+        assert_eq!(osm_ids(kept), vec![Some(2), Some(3)]);
+
+        let kept = remove_duplicate_boundaries(admins(), DuplicatePolicy::LowestOsmId, &budget);
+        assert_eq!(osm_ids(kept), vec![Some(1), Some(3)]);
+        assert!(budget.check().is_ok());
+
+        let kept = remove_duplicate_boundaries(admins(), DuplicatePolicy::Fail, &budget);
+        assert!(kept.is_empty());
+        assert!(budget.check().is_err());
+    }
+
+    #[test]
+    fn hash_collisions() {
+        let unit = || square(vec![(0., 0.), (1., 0.), (1., 1.), (0., 1.), (0., 0.)]);
+        let admins = vec![
+            city("r1", None, unit()),
+            city(
+                "r2",
+                None,
+                square(vec![(2., 2.), (3., 2.), (3., 3.), (2., 2.)]),
+            ),
+            city("r3", None, unit()),
+        ];
+        // every boundary has the same hash, only the equal ones being duplicates
+        assert_eq!(duplicate_groups(&admins, |_| 42), vec![vec![0, 2]]);
+        assert_eq!(duplicate_groups(&admins, boundary_hash), vec![vec![0, 2]]);
+    }
+
+    #[test]
+    fn uri_collisions() {
+        let unit = || square(vec![(0., 0.), (1., 0.), (1., 1.), (0., 1.), (0., 0.)]);
+        let mut collisions = UriCollisions::default();
+        assert_eq!(collisions.check(&city("r1", Some("75056"), unit())), None);
+        assert_eq!(collisions.check(&city("r2", Some("75111"), unit())), None);
+        assert_eq!(
+            collisions.check(&city("r3", Some("75056"), unit())),
+            Some(
+                "r1 (zone 1, relation 1) and r3 (zone 3, relation 3) have the same uri admin:fr:75056"
+                    .to_owned()
+            )
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use failure::{bail, Error};
+use log::info;
+use postgres::{transaction::Transaction, GenericConnection};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// table of the generations imported with --versioned, the active one being
+/// administrative_regions
+const GENERATIONS_TABLE: &str = "administrative_regions_generations";
+
+/// generation of the table found in place of administrative_regions by the first
+/// versioned import
+const UNVERSIONED_TABLE: &str = "administrative_regions_unversioned";
+
+/// an import kept by --versioned
+#[derive(Debug)]
+pub struct Generation {
+    pub table_name: String,
+    pub imported_at: String,
+    pub source_md5: Option<String>,
+    pub nb_cities: i64,
+    pub active: bool,
+}
+
+fn create_generations_table(cnx: &dyn GenericConnection) -> Result<(), Error> {
+    cnx.batch_execute(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (
+            id BIGSERIAL PRIMARY KEY,
+            table_name TEXT NOT NULL UNIQUE,
+            imported_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+            source_md5 TEXT,
+            nb_cities BIGINT NOT NULL,
+            active BOOLEAN NOT NULL DEFAULT FALSE
+        );",
+        GENERATIONS_TABLE
+    ))?;
+    Ok(())
+}
+
+/// rename a table and the indexes named after it
+fn rename_table(transaction: &Transaction, from: &str, to: &str) -> Result<(), Error> {
+    let indexes = transaction.query(
+        "SELECT indexname::text FROM pg_indexes
+        WHERE schemaname = current_schema() AND tablename = $1;",
+        &[&from],
+    )?;
+    transaction.execute(&format!("ALTER TABLE {} RENAME TO {};", from, to), &[])?;
+    for row in indexes.iter() {
+        let index: String = row.get(0);
+        if index.starts_with(from) {
+            let renamed = index.replacen(from, to, 1);
+            transaction.execute(
+                &format!("ALTER INDEX {} RENAME TO {};", index, renamed),
+                &[],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// name of the active generation, the one currently named administrative_regions.
+/// A table not imported with --versioned is recorded as the generation UNVERSIONED_TABLE
+fn active_generation(transaction: &Transaction) -> Result<String, Error> {
+    let rows = transaction.query(
+        &format!("SELECT table_name FROM {} WHERE active;", GENERATIONS_TABLE),
+        &[],
+    )?;
+    if let Some(row) = rows.iter().next() {
+        return Ok(row.get(0));
+    }
+    info!(
+        "administrative_regions kept as the generation {}",
+        UNVERSIONED_TABLE
+    );
+    transaction.execute(
+        &format!(
+            "INSERT INTO {} (table_name, nb_cities, active)
+            SELECT $1, count(*), TRUE FROM administrative_regions;",
+            GENERATIONS_TABLE
+        ),
+        &[&UNVERSIONED_TABLE],
+    )?;
+    Ok(UNVERSIONED_TABLE.to_owned())
+}
+
+/// replace administrative_regions with a generation, the current table getting back the
+/// name of its own generation
+fn switch_to(transaction: &Transaction, table_name: &str) -> Result<(), Error> {
+    let active = active_generation(transaction)?;
+    crate::copy_privileges(transaction, "administrative_regions", table_name)?;
+    rename_table(transaction, "administrative_regions", &active)?;
+    rename_table(transaction, table_name, "administrative_regions")?;
+    transaction.execute(
+        &format!(
+            "UPDATE {} SET active = (table_name = $1);",
+            GENERATIONS_TABLE
+        ),
+        &[&table_name],
+    )?;
+    info!(
+        "administrative_regions is now the generation {}",
+        table_name
+    );
+    Ok(())
+}
+
+/// create the empty table of a new generation, with the columns, constraints and indexes
+/// of administrative_regions
+pub fn create_generation(transaction: &Transaction) -> Result<String, Error> {
+    create_generations_table(transaction)?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    let table_name = format!("administrative_regions_{}", timestamp);
+    info!("loading the cities in the generation {}", table_name);
+    transaction.batch_execute(&format!(
+        "CREATE TABLE {} (LIKE administrative_regions INCLUDING ALL);",
+        table_name
+    ))?;
+    Ok(table_name)
+}
+
+/// record the new generation and make it administrative_regions, then drop the oldest
+/// generations to only keep the last ones. The active generation is never dropped
+pub fn activate_generation(
+    transaction: &Transaction,
+    table_name: &str,
+    source_md5: Option<&str>,
+    keep: usize,
+) -> Result<(), Error> {
+    // the generation replaced is older than the new one
+    active_generation(transaction)?;
+    transaction.execute(
+        &format!(
+            "INSERT INTO {} (table_name, source_md5, nb_cities)
+            SELECT $1, $2, count(*) FROM {};",
+            GENERATIONS_TABLE, table_name
+        ),
+        &[&table_name, &source_md5],
+    )?;
+    switch_to(transaction, table_name)?;
+
+    let expired = transaction.query(
+        &format!(
+            "SELECT table_name FROM {} WHERE NOT active ORDER BY id DESC OFFSET $1;",
+            GENERATIONS_TABLE
+        ),
+        &[&(keep.saturating_sub(1) as i64)],
+    )?;
+    for row in expired.iter() {
+        let expired: String = row.get(0);
+        info!("dropping the generation {}", expired);
+        transaction.execute(&format!("DROP TABLE IF EXISTS {};", expired), &[])?;
+        transaction.execute(
+            &format!("DELETE FROM {} WHERE table_name = $1;", GENERATIONS_TABLE),
+            &[&expired],
+        )?;
+    }
+    Ok(())
+}
+
+/// make the generation imported before the active one administrative_regions again
+pub fn rollback(transaction: &Transaction) -> Result<String, Error> {
+    create_generations_table(transaction)?;
+    let rows = transaction.query(
+        &format!(
+            "SELECT g.table_name FROM {table} g
+            WHERE g.id < (SELECT id FROM {table} WHERE active)
+            AND to_regclass(g.table_name) IS NOT NULL
+            ORDER BY g.id DESC LIMIT 1;",
+            table = GENERATIONS_TABLE
+        ),
+        &[],
+    )?;
+    let previous: String = match rows.iter().next() {
+        Some(row) => row.get(0),
+        None => bail!("there is no previous generation of administrative_regions to roll back to"),
+    };
+    switch_to(transaction, &previous)?;
+    Ok(previous)
+}
+
+/// the generations kept, from the most recent one
+pub fn list_generations(cnx: &dyn GenericConnection) -> Result<Vec<Generation>, Error> {
+    create_generations_table(cnx)?;
+    let rows = cnx.query(
+        &format!(
+            "SELECT table_name, imported_at::text, source_md5, nb_cities, active
+            FROM {} ORDER BY id DESC;",
+            GENERATIONS_TABLE
+        ),
+        &[],
+    )?;
+    Ok(rows
+        .iter()
+        .map(|row| Generation {
+            table_name: row.get(0),
+            imported_at: row.get(1),
+            source_md5: row.get(2),
+            nb_cities: row.get(3),
+            active: row.get(4),
+        })
+        .collect())
+}
@@ -0,0 +1,153 @@
+use cosmogony::{Zone, ZoneType};
+use std::str::FromStr;
+
+const ZONE_TYPES: &[ZoneType] = &[
+    ZoneType::Suburb,
+    ZoneType::CityDistrict,
+    ZoneType::City,
+    ZoneType::StateDistrict,
+    ZoneType::State,
+    ZoneType::CountryRegion,
+    ZoneType::Country,
+];
+
+/// admin levels expected for each zone type, wide enough for the countries where the cities
+/// are not at level 8
+const DEFAULT_LEVEL_RANGES: &[(ZoneType, u32, u32)] = &[
+    (ZoneType::Suburb, 9, 11),
+    (ZoneType::CityDistrict, 9, 10),
+    (ZoneType::City, 6, 10),
+    (ZoneType::StateDistrict, 5, 7),
+    (ZoneType::State, 3, 6),
+    (ZoneType::CountryRegion, 3, 4),
+    (ZoneType::Country, 2, 2),
+];
+
+/// the admin levels expected for a zone type, both included
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelRange {
+    pub zone_type: ZoneType,
+    pub min: u32,
+    pub max: u32,
+}
+
+impl FromStr for LevelRange {
+    type Err = String;
+
+    /// a zone type and a range of levels, like city:6-10, or city:8 for a single level
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid level range '{}', it should be like city:6-10 or city:8",
+                s
+            )
+        };
+        let mut parts = s.splitn(2, ':');
+        let (zone_type, levels) = match (parts.next(), parts.next()) {
+            (Some(zone_type), Some(levels)) => (zone_type, levels),
+            _ => return Err(invalid()),
+        };
+        let zone_type = *ZONE_TYPES
+            .iter()
+            .find(|t| t.as_str() == zone_type.trim())
+            .ok_or_else(|| format!("unknown zone type '{}'", zone_type))?;
+        let mut bounds = levels.splitn(2, '-').map(|l| l.trim().parse::<u32>());
+        let (min, max) = match (bounds.next(), bounds.next()) {
+            (Some(Ok(min)), None) => (min, min),
+            (Some(Ok(min)), Some(Ok(max))) if min <= max => (min, max),
+            _ => return Err(invalid()),
+        };
+        Ok(Self {
+            zone_type,
+            min,
+            max,
+        })
+    }
+}
+
+/// the expected range of the zone type, the one given in `ranges` or the default one
+fn expected_range(zone_type: ZoneType, ranges: &[LevelRange]) -> Option<(u32, u32)> {
+    ranges
+        .iter()
+        .rev()
+        .find(|r| r.zone_type == zone_type)
+        .map(|r| (r.min, r.max))
+        .or_else(|| {
+            DEFAULT_LEVEL_RANGES
+                .iter()
+                .find(|(t, _, _)| *t == zone_type)
+                .map(|&(_, min, max)| (min, max))
+        })
+}
+
+/// description of the inconsistency if the admin level of the zone is not in the range
+/// of its zone type, the zones without type or level being never inconsistent
+pub fn inconsistency(zone: &Zone, ranges: &[LevelRange]) -> Option<String> {
+    let (zone_type, level) = (zone.zone_type?, zone.admin_level?);
+    let (min, max) = expected_range(zone_type, ranges)?;
+    if min <= level && level <= max {
+        return None;
+    }
+    Some(format!(
+        "zone {} ({}) is a {} of admin level {}, a level between {} and {} was expected",
+        zone.name,
+        zone.osm_id,
+        zone_type.as_str(),
+        level,
+        min,
+        max
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn zone(zone_type: ZoneType, admin_level: u32) -> Zone {
+        let mut zone = Zone::default();
+        zone.name = "Paris".to_owned();
+        zone.osm_id = "relation:7444".to_owned();
+        zone.zone_type = Some(zone_type);
+        zone.admin_level = Some(admin_level);
+        zone
+    }
+
+    #[test]
+    fn parse_level_range() {
+        assert_eq!(
+            "city:6-10".parse(),
+            Ok(LevelRange {
+                zone_type: ZoneType::City,
+                min: 6,
+                max: 10
+            })
+        );
+        assert_eq!(
+            "city_district:9".parse(),
+            Ok(LevelRange {
+                zone_type: ZoneType::CityDistrict,
+                min: 9,
+                max: 9
+            })
+        );
+        assert!("town:8".parse::<LevelRange>().is_err());
+        assert!("city:10-6".parse::<LevelRange>().is_err());
+        assert!("city".parse::<LevelRange>().is_err());
+    }
+
+    #[test]
+    fn level_inconsistencies() {
+        assert_eq!(inconsistency(&zone(ZoneType::City, 8), &[]), None);
+        assert_eq!(
+            inconsistency(&zone(ZoneType::City, 4), &[]),
+            Some(
+                "zone Paris (relation:7444) is a city of admin level 4, \
+                 a level between 6 and 10 was expected"
+                    .to_owned()
+            )
+        );
+        let ranges = vec!["city:4-8".parse().unwrap()];
+        assert_eq!(inconsistency(&zone(ZoneType::City, 4), &ranges), None);
+        assert!(inconsistency(&zone(ZoneType::NonAdministrative, 4), &ranges).is_none());
+    }
+}
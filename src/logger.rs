@@ -0,0 +1,450 @@
+use env_logger::fmt::WriteStyle;
+use env_logger::{Builder, Env};
+use failure::Error;
+use log::{warn, Level, Record};
+use std::borrow::Cow;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+const IDENTIFIER: &str = "cosmogony2cities";
+
+/// true when the logs on stderr are colorized
+static COLORED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogTarget {
+    Stderr,
+    Syslog,
+    Journald,
+}
+
+impl FromStr for LogTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stderr" => Ok(LogTarget::Stderr),
+            "syslog" => Ok(LogTarget::Syslog),
+            "journald" => Ok(LogTarget::Journald),
+            _ => Err(format!(
+                "invalid log target '{}', it should be stderr, syslog or journald",
+                s
+            )),
+        }
+    }
+}
+
+/// when to colorize the logs on stderr, auto only colorizing them on a terminal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorOutput {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorOutput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorOutput::Auto),
+            "always" => Ok(ColorOutput::Always),
+            "never" => Ok(ColorOutput::Never),
+            _ => Err(format!(
+                "invalid color output '{}', it should be auto, always or never",
+                s
+            )),
+        }
+    }
+}
+
+impl ColorOutput {
+    fn enabled(self) -> bool {
+        match self {
+            ColorOutput::Auto => atty::is(atty::Stream::Stderr),
+            ColorOutput::Always => true,
+            ColorOutput::Never => false,
+        }
+    }
+}
+
+/// the value in cyan when the logs are colorized, to highlight the counts of the summaries
+pub fn highlight<T: fmt::Display>(value: T) -> String {
+    if COLORED.load(Ordering::Relaxed) {
+        format!("\x1b[36m{}\x1b[0m", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// the line without its ANSI color sequences
+fn strip_colors(line: &str) -> Cow<str> {
+    if !line.contains('\x1b') {
+        return Cow::Borrowed(line);
+    }
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // skip the sequence up to its final letter, like the m of \x1b[36m
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            stripped.push(c);
+        }
+    }
+    Cow::Owned(stripped)
+}
+
+/// line of a record in the log file, without the colors of its message
+fn file_line(timestamp: impl fmt::Display, record: &Record) -> String {
+    format!(
+        "[{} {:<5} {}] {}",
+        timestamp,
+        record.level(),
+        record.target(),
+        strip_colors(&record.args().to_string())
+    )
+}
+
+/// syslog severity (also used by journald) of a log level
+pub fn priority(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+#[cfg(unix)]
+fn open_log_file(path: &str) -> std::io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o640)
+        .open(path)
+}
+
+#[cfg(not(unix))]
+fn open_log_file(path: &str) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// log file renamed path.1 when it would exceed max_size bytes, the previous path.1 being
+/// renamed path.2 and so on up to path.{keep}, the older files being removed
+struct RotatingFile {
+    path: String,
+    writer: LineWriter<File>,
+    size: u64,
+    max_size: Option<u64>,
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn open(path: &str, max_size: Option<u64>, keep: NonZeroUsize) -> std::io::Result<Self> {
+        let file = open_log_file(path)?;
+        Ok(Self {
+            path: path.to_owned(),
+            size: file.metadata()?.len(),
+            writer: LineWriter::new(file),
+            max_size,
+            keep: keep.get(),
+        })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.writer.flush()?;
+        let rotated = |n: usize| format!("{}.{}", self.path, n);
+        let nb_rotated = (1..)
+            .take_while(|&n| Path::new(&rotated(n)).exists())
+            .count();
+        // the files from path.{keep} would be renamed beyond it
+        for n in self.keep..=nb_rotated {
+            std::fs::remove_file(rotated(n))?;
+        }
+        for n in (1..self.keep.min(nb_rotated + 1)).rev() {
+            std::fs::rename(rotated(n), rotated(n + 1))?;
+        }
+        std::fs::rename(&self.path, rotated(1))?;
+        self.writer = LineWriter::new(open_log_file(&self.path)?);
+        self.size = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if let Some(max_size) = self.max_size {
+            if self.size > 0 && self.size + len > max_size {
+                self.rotate()?;
+            }
+        }
+        writeln!(self.writer, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+mod socket {
+    use super::{file_line, priority, LogTarget, RotatingFile, IDENTIFIER};
+    use log::{Log, Metadata, Record};
+    use std::os::unix::net::UnixDatagram;
+    use std::sync::Mutex;
+    use std::time::SystemTime;
+
+    /// logger sending the records to the local syslog or journald socket, and to the log
+    /// file if any
+    pub struct SocketLogger {
+        socket: UnixDatagram,
+        target: LogTarget,
+        filter: env_logger::filter::Filter,
+        pub file: Option<Mutex<RotatingFile>>,
+    }
+
+    impl SocketLogger {
+        pub fn connect(
+            target: LogTarget,
+            filter: env_logger::filter::Filter,
+        ) -> std::io::Result<Self> {
+            let path = match target {
+                LogTarget::Journald => "/run/systemd/journal/socket",
+                _ => "/dev/log",
+            };
+            let socket = UnixDatagram::unbound()?;
+            socket.connect(path)?;
+            Ok(Self {
+                socket,
+                target,
+                filter,
+                file: None,
+            })
+        }
+
+        pub fn level_filter(&self) -> log::LevelFilter {
+            self.filter.filter()
+        }
+    }
+
+    impl Log for SocketLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            self.filter.enabled(metadata)
+        }
+
+        fn log(&self, record: &Record) {
+            if !self.filter.matches(record) {
+                return;
+            }
+            let msg = match self.target {
+                LogTarget::Journald => format!(
+                    "PRIORITY={}\nSYSLOG_IDENTIFIER={}\nCODE_MODULE={}\nMESSAGE={}\n",
+                    priority(record.level()),
+                    IDENTIFIER,
+                    record.target(),
+                    record.args().to_string().replace('\n', " ")
+                ),
+                // user-level messages facility
+                _ => format!(
+                    "<{}>{}[{}]: {}",
+                    8 + priority(record.level()),
+                    IDENTIFIER,
+                    std::process::id(),
+                    record.args()
+                ),
+            };
+            let _ = self.socket.send(msg.as_bytes());
+            if let Some(Ok(mut file)) = self.file.as_ref().map(Mutex::lock) {
+                let timestamp = humantime::format_rfc3339_seconds(SystemTime::now());
+                let _ = file.write_line(&file_line(timestamp, record));
+            }
+        }
+
+        fn flush(&self) {
+            if let Some(Ok(mut file)) = self.file.as_ref().map(Mutex::lock) {
+                let _ = std::io::Write::flush(&mut file.writer);
+            }
+        }
+    }
+}
+
+/// the file is only taken once connected to the socket, to be used by the stderr logger
+/// otherwise
+#[cfg(unix)]
+fn init_socket_logger(target: LogTarget, file: &mut Option<RotatingFile>) -> Result<(), Error> {
+    let filter = env_logger::filter::Builder::new()
+        .parse(&std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_owned()))
+        .build();
+    let mut logger = socket::SocketLogger::connect(target, filter)?;
+    logger.file = file.take().map(Mutex::new);
+    log::set_max_level(logger.level_filter());
+    log::set_boxed_logger(Box::new(logger))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn init_socket_logger(target: LogTarget, _file: &mut Option<RotatingFile>) -> Result<(), Error> {
+    failure::bail!("{:?} is only available on unix", target)
+}
+
+fn init_stderr_logger(file: Option<RotatingFile>, color: ColorOutput) -> Result<(), Error> {
+    let colored = color.enabled();
+    COLORED.store(colored, Ordering::Relaxed);
+    let mut builder = Builder::from_env(Env::default().default_filter_or("info"));
+    // the levels are colored by env_logger: info in green, warn in yellow and error in red
+    builder.write_style(if colored {
+        WriteStyle::Always
+    } else {
+        WriteStyle::Never
+    });
+    if let Some(file) = file {
+        let file = Mutex::new(file);
+        builder.format(move |buf, record| {
+            let timestamp = buf.timestamp();
+            if let Ok(mut file) = file.lock() {
+                file.write_line(&file_line(&timestamp, record))?;
+            }
+            writeln!(
+                buf,
+                "[{} {:<5} {}] {}",
+                timestamp,
+                buf.default_styled_level(record.level()),
+                record.target(),
+                record.args()
+            )
+        });
+    }
+    builder.init();
+    Ok(())
+}
+
+/// initialize the logger, falling back to stderr when syslog or journald is not available.
+/// The logs are also written in the log file whatever the target, the file being rotated
+/// when it reaches rotate_size_mb, only rotate_keep previous files being kept. Only the logs
+/// on stderr are colorized
+pub fn init(
+    target: LogTarget,
+    log_file: Option<&str>,
+    rotate_size_mb: Option<u64>,
+    rotate_keep: NonZeroUsize,
+    color: ColorOutput,
+) -> Result<(), Error> {
+    let mut file = log_file
+        .map(|path| {
+            RotatingFile::open(path, rotate_size_mb.map(|mb| mb * 1024 * 1024), rotate_keep)
+                .map_err(|e| failure::format_err!("impossible to open log file {}: {}", path, e))
+        })
+        .transpose()?;
+    if target == LogTarget::Stderr {
+        return init_stderr_logger(file, color);
+    }
+    match init_socket_logger(target, &mut file) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            init_stderr_logger(file, color)?;
+            warn!(
+                "impossible to log to {:?}, logging on stderr: {}",
+                target, e
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn log_targets() {
+        assert_eq!("journald".parse::<LogTarget>(), Ok(LogTarget::Journald));
+        assert_eq!("syslog".parse::<LogTarget>(), Ok(LogTarget::Syslog));
+        assert!("kafka".parse::<LogTarget>().is_err());
+    }
+
+    #[test]
+    fn color_outputs() {
+        assert_eq!("never".parse::<ColorOutput>(), Ok(ColorOutput::Never));
+        assert_eq!("always".parse::<ColorOutput>(), Ok(ColorOutput::Always));
+        assert!("rainbow".parse::<ColorOutput>().is_err());
+        assert_eq!(
+            strip_colors("\x1b[36m12\x1b[0m cities converted"),
+            "12 cities converted"
+        );
+        assert_eq!(strip_colors("no color"), "no color");
+    }
+
+    #[test]
+    fn log_file_lines() {
+        let record = |level| {
+            file_line(
+                "2019-07-01T12:00:00Z",
+                &Record::builder()
+                    .level(level)
+                    .target("cosmogony2cities")
+                    .args(format_args!("\x1b[36m12\x1b[0m cities converted"))
+                    .build(),
+            )
+        };
+        assert_eq!(
+            record(Level::Info),
+            "[2019-07-01T12:00:00Z INFO  cosmogony2cities] 12 cities converted"
+        );
+        assert_eq!(
+            record(Level::Error),
+            "[2019-07-01T12:00:00Z ERROR cosmogony2cities] 12 cities converted"
+        );
+    }
+
+    #[test]
+    fn level_priorities() {
+        assert_eq!(priority(Level::Error), 3);
+        assert_eq!(priority(Level::Warn), 4);
+        assert_eq!(priority(Level::Info), 6);
+        assert_eq!(priority(Level::Debug), 7);
+        assert_eq!(priority(Level::Trace), 7);
+    }
+
+    #[test]
+    fn log_file_rotation() {
+        let dir = crate::test_utils::temp_path("logs");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("import.log").to_string_lossy().into_owned();
+        let read = |suffix: &str| std::fs::read_to_string(format!("{}{}", path, suffix)).unwrap();
+
+        let keep = NonZeroUsize::new(2).unwrap();
+        let mut file = RotatingFile::open(&path, Some(10), keep).unwrap();
+        for line in &["first", "second", "third", "4"] {
+            file.write_line(line).unwrap();
+        }
+        file.writer.flush().unwrap();
+
+        assert_eq!(read(""), "third\n4\n");
+        assert_eq!(read(".1"), "second\n");
+        assert_eq!(read(".2"), "first\n");
+
+        // only 2 previous files are kept
+        file.write_line("fifth").unwrap();
+        file.writer.flush().unwrap();
+        assert_eq!(read(""), "fifth\n");
+        assert_eq!(read(".1"), "third\n4\n");
+        assert_eq!(read(".2"), "second\n");
+        assert!(!Path::new(&format!("{}.3", path)).exists());
+
+        // the files left by a larger keep are removed
+        std::fs::write(format!("{}.3", path), "older\n").unwrap();
+        let keep = NonZeroUsize::new(1).unwrap();
+        let mut file = RotatingFile::open(&path, Some(10), keep).unwrap();
+        file.write_line("sixth").unwrap();
+        file.writer.flush().unwrap();
+        assert_eq!(read(".1"), "fifth\n");
+        assert!(!Path::new(&format!("{}.2", path)).exists());
+        assert!(!Path::new(&format!("{}.3", path)).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
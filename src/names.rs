@@ -0,0 +1,97 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::str::FromStr;
+
+/// values used in OSM in place of a real name
+const PLACEHOLDER_NAMES: &[&str] = &["?", "FIXME", "TODO"];
+
+lazy_static! {
+    /// the common prefixes of the French city names, with their separator
+    static ref NAME_PREFIX: Regex =
+        Regex::new(r"^(?i:saint-|sainte-|saint |sainte |les |le |la |l'|l’)").unwrap();
+}
+
+/// what to do with a city whose name is empty or a placeholder
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnEmptyName {
+    Skip,
+    Keep,
+    Fail,
+}
+
+impl FromStr for OnEmptyName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(OnEmptyName::Skip),
+            "keep" => Ok(OnEmptyName::Keep),
+            "fail" => Ok(OnEmptyName::Fail),
+            _ => Err(format!(
+                "invalid value '{}', it should be skip, keep or fail",
+                s
+            )),
+        }
+    }
+}
+
+/// true if the name is empty once trimmed, or is a placeholder value
+/// (compared case insensitively with the default ones and the `denylist`)
+pub fn is_placeholder(name: &str, denylist: &[String]) -> bool {
+    let name = name.trim();
+    name.is_empty()
+        || PLACEHOLDER_NAMES
+            .iter()
+            .copied()
+            .chain(denylist.iter().map(String::as_str))
+            .any(|placeholder| placeholder.trim().eq_ignore_ascii_case(name))
+}
+
+/// the name without its first word when it is a common prefix like Saint- or Le, the
+/// whole name if nothing follows the prefix
+pub fn without_prefix(name: &str) -> &str {
+    match NAME_PREFIX.find(name) {
+        Some(prefix) if prefix.end() < name.trim_end().len() => &name[prefix.end()..],
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn placeholder_names() {
+        let denylist = vec!["N/A".to_string()];
+        assert!(is_placeholder("", &denylist));
+        assert!(is_placeholder(" \t ", &denylist));
+        assert!(is_placeholder("?", &denylist));
+        assert!(is_placeholder(" fixme ", &denylist));
+        assert!(is_placeholder("n/a", &denylist));
+        assert!(!is_placeholder("n/a", &[]));
+        assert!(!is_placeholder("Paris", &denylist));
+        assert!(!is_placeholder("L'Haÿ-les-Roses", &denylist));
+    }
+
+    #[test]
+    fn name_prefixes() {
+        assert_eq!(without_prefix("Saint-Martin"), "Martin");
+        assert_eq!(without_prefix("Sainte-Marie"), "Marie");
+        assert_eq!(without_prefix("Saint Pierre"), "Pierre");
+        assert_eq!(without_prefix("Les Sables-d'Olonne"), "Sables-d'Olonne");
+        assert_eq!(without_prefix("Le Mans"), "Mans");
+        assert_eq!(without_prefix("La Rochelle"), "Rochelle");
+        assert_eq!(without_prefix("L'Haÿ-les-Roses"), "Haÿ-les-Roses");
+        assert_eq!(without_prefix("L’Isle-Adam"), "Isle-Adam");
+        // only the first prefix is removed
+        assert_eq!(without_prefix("Saint-Jean-de-Luz"), "Jean-de-Luz");
+        assert_eq!(
+            without_prefix("Les Saintes-Maries-de-la-Mer"),
+            "Saintes-Maries-de-la-Mer"
+        );
+        assert_eq!(without_prefix("Lens"), "Lens");
+        assert_eq!(without_prefix("Laval"), "Laval");
+        assert_eq!(without_prefix("Saintes"), "Saintes");
+        assert_eq!(without_prefix("Le "), "Le ");
+    }
+}
@@ -0,0 +1,157 @@
+use crate::AdministrativeRegion;
+use geo_types::{LineString, MultiPolygon};
+use serde_json::{json, Map, Value};
+
+fn ring_coordinates(ring: &LineString<f64>) -> Value {
+    ring.0.iter().map(|c| json!([c.x, c.y])).collect()
+}
+
+fn geojson_multipolygon(boundary: &MultiPolygon<f64>) -> Value {
+    let coordinates: Vec<Value> = boundary
+        .0
+        .iter()
+        .map(|polygon| {
+            std::iter::once(polygon.exterior())
+                .chain(polygon.interiors())
+                .map(ring_coordinates)
+                .collect()
+        })
+        .collect();
+    json!({"type": "MultiPolygon", "coordinates": coordinates})
+}
+
+/// [min lat, max lat, min lon, max lon], as strings like Nominatim
+fn bounding_box(boundary: &MultiPolygon<f64>) -> Option<Value> {
+    let mut coords = boundary
+        .0
+        .iter()
+        .flat_map(|polygon| polygon.exterior().0.iter());
+    let first = coords.next()?;
+    let (min_lat, max_lat, min_lon, max_lon) = coords.fold(
+        (first.y, first.y, first.x, first.x),
+        |(min_lat, max_lat, min_lon, max_lon), c| {
+            (
+                min_lat.min(c.y),
+                max_lat.max(c.y),
+                min_lon.min(c.x),
+                max_lon.max(c.x),
+            )
+        },
+    );
+    Some(json!([
+        min_lat.to_string(),
+        max_lat.to_string(),
+        min_lon.to_string(),
+        max_lon.to_string()
+    ]))
+}
+
+impl AdministrativeRegion {
+    /// the city as a place of the Nominatim API, for the applications migrating from Nominatim.
+    /// Only the OSM relations have an osm_type and osm_id, and the geojson is the boundary,
+    /// or the coord when there is no boundary
+    pub fn to_nominatim_json(&self) -> Value {
+        let mut address = Map::new();
+        address.insert("city".to_owned(), json!(self.name));
+        if let Some(post_code) = &self.post_code {
+            address.insert("postcode".to_owned(), json!(post_code));
+        }
+        if self.uri.starts_with("admin:fr:") {
+            address.insert("country_code".to_owned(), json!("fr"));
+        }
+
+        let geojson = match (&self.boundary, &self.coord) {
+            (Some(boundary), _) => geojson_multipolygon(boundary),
+            (None, Some(coord)) => json!({"type": "Point", "coordinates": [coord.x(), coord.y()]}),
+            (None, None) => Value::Null,
+        };
+
+        json!({
+            "place_id": self.id,
+            "osm_type": self.osm_relation_id.map(|_| "relation"),
+            "osm_id": self.osm_relation_id,
+            "lat": self.coord.map(|c| c.y().to_string()),
+            "lon": self.coord.map(|c| c.x().to_string()),
+            "display_name": self.name,
+            "class": "boundary",
+            "type": "administrative",
+            "address": address,
+            "boundingbox": self.boundary.as_ref().and_then(bounding_box),
+            "geojson": geojson,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nominatim_json() {
+        // Châtillon (92), as returned by nominatim/search?format=json&addressdetails=1&polygon_geojson=1
+        let mut zone = cosmogony::Zone::default();
+        zone.id = cosmogony::ZoneIndex { index: 42 };
+        zone.name = "Châtillon".to_owned();
+        zone.osm_id = "relation:8486".to_owned();
+        zone.tags = vec![("ref:INSEE", "92020"), ("addr:postcode", "92320")]
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v.to_owned()))
+            .collect();
+        zone.center = Some((2.2931, 48.8034).into());
+        zone.boundary = Some(MultiPolygon(vec![geo_types::Polygon::new(
+            vec![
+                (2.2768, 48.7985),
+                (2.3056, 48.7985),
+                (2.3056, 48.8131),
+                (2.2768, 48.8131),
+                (2.2768, 48.7985),
+            ]
+            .into(),
+            vec![],
+        )]));
+
+        let place = AdministrativeRegion::from(zone).to_nominatim_json();
+        assert_eq!(
+            place,
+            json!({
+                "place_id": 42,
+                "osm_type": "relation",
+                "osm_id": 8486,
+                "lat": "48.8034",
+                "lon": "2.2931",
+                "display_name": "Châtillon",
+                "class": "boundary",
+                "type": "administrative",
+                "address": {"city": "Châtillon", "postcode": "92320", "country_code": "fr"},
+                "boundingbox": ["48.7985", "48.8131", "2.2768", "2.3056"],
+                "geojson": {
+                    "type": "MultiPolygon",
+                    "coordinates": [[[
+                        [2.2768, 48.7985],
+                        [2.3056, 48.7985],
+                        [2.3056, 48.8131],
+                        [2.2768, 48.8131],
+                        [2.2768, 48.7985]
+                    ]]]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn nominatim_json_without_boundary() {
+        let mut zone = cosmogony::Zone::default();
+        zone.name = "Hameau".to_owned();
+        zone.osm_id = "way:12".to_owned();
+        zone.center = Some((1.5, 2.5).into());
+
+        let place = AdministrativeRegion::from(zone).to_nominatim_json();
+        assert_eq!(place["osm_type"], Value::Null);
+        assert_eq!(place["boundingbox"], Value::Null);
+        assert_eq!(place["address"], json!({"city": "Hameau"}));
+        assert_eq!(
+            place["geojson"],
+            json!({"type": "Point", "coordinates": [1.5, 2.5]})
+        );
+    }
+}
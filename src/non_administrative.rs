@@ -0,0 +1,71 @@
+use crate::{osm_relation_id, to_wkt};
+use cosmogony::Zone;
+use geo_types::{MultiPolygon, Point};
+use postgres::types::ToSql;
+
+/// OSM tags giving the category of a non administrative zone, by priority
+const CATEGORY_TAGS: &[&str] = &["leisure", "landuse", "natural"];
+
+/// columns of the non_administrative_regions table with the SQL expression used to insert them,
+/// like crate::COLUMNS
+pub const COLUMNS: &[(&str, &str)] = &[
+    ("id", "$"),
+    ("name", "$"),
+    ("uri", "$"),
+    ("coord", "ST_GeomFromText($)"),
+    ("boundary", "ST_GeomFromText($)"),
+    ("osm_relation_id", "$"),
+    ("category", "$"),
+];
+
+/// a park, a nature reserve..., imported in non_administrative_regions
+pub struct NonAdministrativeRegion {
+    id: i64,
+    name: String,
+    uri: String,
+    coord: Option<Point<f64>>,
+    boundary: Option<MultiPolygon<f64>>,
+    osm_relation_id: Option<i64>,
+    category: Option<String>,
+}
+
+impl From<Zone> for NonAdministrativeRegion {
+    fn from(zone: Zone) -> Self {
+        let category = CATEGORY_TAGS
+            .iter()
+            .find_map(|tag| zone.tags.get(*tag))
+            .map(|v| v.to_string());
+        Self {
+            id: zone.id.index as i64,
+            uri: format!("admin:osm:{}", zone.osm_id),
+            osm_relation_id: osm_relation_id(&zone.osm_id),
+            name: zone.name,
+            coord: zone.center,
+            boundary: zone.boundary,
+            category,
+        }
+    }
+}
+
+impl NonAdministrativeRegion {
+    /// the values of the COLUMNS with their approximate size in bytes, like
+    /// AdministrativeRegion::into_sized_sql_params
+    pub fn into_sized_sql_params(self) -> (usize, Vec<Box<dyn ToSql + Send + Sync>>) {
+        let coord = self.coord.map(to_wkt);
+        let boundary = self.boundary.map(to_wkt);
+        let size = coord.as_ref().map_or(0, String::len)
+            + boundary.as_ref().map_or(0, String::len)
+            + self.name.len()
+            + self.uri.len();
+        let params: Vec<Box<dyn ToSql + Send + Sync>> = vec![
+            Box::new(self.id),
+            Box::new(self.name),
+            Box::new(self.uri),
+            Box::new(coord),
+            Box::new(boundary),
+            Box::new(self.osm_relation_id),
+            Box::new(self.category),
+        ];
+        (size, params)
+    }
+}
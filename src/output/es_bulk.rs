@@ -0,0 +1,216 @@
+use super::OutputWriter;
+use crate::AdministrativeRegion;
+use failure::Error;
+use geo_types::{LineString, MultiPolygon, Polygon};
+use serde::{Serialize, Serializer};
+use serde_json::json;
+use std::io::{BufWriter, Write};
+
+/// coordinates of a ring, serialized one point at a time
+struct Ring<'a>(&'a LineString<f64>);
+
+impl Serialize for Ring<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0 .0.iter().map(|c| [c.x, c.y]))
+    }
+}
+
+struct Rings<'a>(&'a Polygon<f64>);
+
+impl Serialize for Rings<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            std::iter::once(self.0.exterior())
+                .chain(self.0.interiors())
+                .map(Ring),
+        )
+    }
+}
+
+struct Polygons<'a>(&'a MultiPolygon<f64>);
+
+impl Serialize for Polygons<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0 .0.iter().map(Rings))
+    }
+}
+
+/// GeoJSON MultiPolygon written directly in the output, without building it in memory
+#[derive(Serialize)]
+struct GeoJsonMultiPolygon<'a> {
+    #[serde(rename = "type")]
+    geometry_type: &'static str,
+    coordinates: Polygons<'a>,
+}
+
+#[derive(Serialize)]
+struct LatLon {
+    lat: f64,
+    lon: f64,
+}
+
+/// an admin as indexed by mimirsbrunn
+#[derive(Serialize)]
+struct MimirAdmin<'a> {
+    id: &'a str,
+    insee: Option<&'a str>,
+    level: Option<i32>,
+    label: String,
+    name: &'a str,
+    zip_codes: &'a [String],
+    coord: Option<LatLon>,
+    boundary: Option<GeoJsonMultiPolygon<'a>>,
+    weight: Option<f64>,
+    zone_type: &'static str,
+}
+
+impl<'a> From<&'a AdministrativeRegion> for MimirAdmin<'a> {
+    fn from(admin: &'a AdministrativeRegion) -> Self {
+        let label = match &admin.post_code {
+            Some(post_code) => format!("{} ({})", admin.name, post_code),
+            None => admin.name.clone(),
+        };
+        Self {
+            id: &admin.uri,
+            insee: admin.insee.as_deref(),
+            level: admin.level,
+            label,
+            name: &admin.name,
+            zip_codes: &admin.zip_codes,
+            coord: admin.coord.map(|c| LatLon {
+                lat: c.y(),
+                lon: c.x(),
+            }),
+            boundary: admin.boundary.as_ref().map(|b| GeoJsonMultiPolygon {
+                geometry_type: "MultiPolygon",
+                coordinates: Polygons(b),
+            }),
+            // computed by mimirsbrunn
+            weight: None,
+            zone_type: "city",
+        }
+    }
+}
+
+/// write an Elasticsearch bulk file, an index action followed by the mimir admin document
+/// for each admin, the index being given in the URL of the bulk request
+pub struct EsBulkWriter<W: Write> {
+    writer: BufWriter<W>,
+}
+
+impl<W: Write> EsBulkWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+        }
+    }
+}
+
+impl<W: Write> OutputWriter for EsBulkWriter<W> {
+    fn extension(&self) -> &'static str {
+        "ndjson"
+    }
+
+    fn write(&mut self, admin: &AdministrativeRegion) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.writer, &json!({"index": {"_id": admin.uri}}))?;
+        self.writer.write_all(b"\n")?;
+        serde_json::to_writer(&mut self.writer, &MimirAdmin::from(admin))?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::Value;
+
+    fn has_type(value: &Value, expected: &str) -> bool {
+        match expected {
+            "null" => value.is_null(),
+            "string" => value.is_string(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "array" => value.is_array(),
+            "object" => value.is_object(),
+            _ => false,
+        }
+    }
+
+    /// the subset of JSON schema used by es_admin.schema.json: type, required and properties
+    fn validate(value: &Value, schema: &Value, path: &str) {
+        let types = match &schema["type"] {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        assert!(
+            types.is_empty() || types.iter().any(|t| has_type(value, t)),
+            "{} should be of type {:?}: {}",
+            path,
+            types,
+            value
+        );
+        if !value.is_object() {
+            return;
+        }
+        for field in schema["required"].as_array().into_iter().flatten() {
+            let field = field.as_str().unwrap();
+            assert!(value.get(field).is_some(), "{}.{} is missing", path, field);
+        }
+        if let Some(properties) = schema["properties"].as_object() {
+            for (field, property) in properties {
+                if let Some(v) = value.get(field) {
+                    validate(v, property, &format!("{}.{}", path, field));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn es_bulk() {
+        let mut zone = crate::test_utils::sample_zone();
+        zone.name = "Paris 11e".to_owned();
+        zone.osm_id = "r9534".to_owned();
+        zone.tags = vec![("ref:INSEE", "75111"), ("addr:postcode", "75011;75111")]
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v.to_owned()))
+            .collect();
+        zone.center = Some((2.38, 48.86).into());
+        let admins = vec![
+            AdministrativeRegion::from(zone),
+            AdministrativeRegion::from(cosmogony::Zone::default()),
+        ];
+
+        let mut buffer = vec![];
+        crate::output::write_all(admins.into_iter(), &mut EsBulkWriter::new(&mut buffer)).unwrap();
+        let lines: Vec<Value> = String::from_utf8(buffer)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+
+        assert_eq!(lines[0], json!({"index": {"_id": "admin:fr:75111"}}));
+        let schema: Value = serde_json::from_str(include_str!("es_admin.schema.json")).unwrap();
+        validate(&lines[1], &schema, "admin");
+        validate(&lines[3], &schema, "admin");
+
+        assert_eq!(lines[1]["label"], "Paris 11e (75011-75111)");
+        assert_eq!(lines[1]["zip_codes"], json!(["75011", "75111"]));
+        assert_eq!(lines[1]["coord"], json!({"lat": 48.86, "lon": 2.38}));
+        assert_eq!(
+            lines[1]["boundary"],
+            json!({
+                "type": "MultiPolygon",
+                "coordinates": [[[[0., 0.], [1., 0.], [1., 1.], [0., 0.]]]]
+            })
+        );
+        assert_eq!(lines[1]["weight"], Value::Null);
+    }
+}
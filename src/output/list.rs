@@ -0,0 +1,172 @@
+use super::{csv_field, OutputWriter};
+use crate::AdministrativeRegion;
+use failure::Error;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+
+const COLUMNS: [&str; 7] = [
+    "id",
+    "name",
+    "insee",
+    "post_code",
+    "level",
+    "has_boundary",
+    "uri",
+];
+
+/// format of the cities printed by --list
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+impl FromStr for ListFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(ListFormat::Table),
+            "json" => Ok(ListFormat::Json),
+            "csv" => Ok(ListFormat::Csv),
+            _ => Err(format!(
+                "invalid list format '{}', it should be table, json or csv",
+                s
+            )),
+        }
+    }
+}
+
+fn row(admin: &AdministrativeRegion) -> [String; 7] {
+    [
+        admin.id.to_string(),
+        admin.name.clone(),
+        admin.insee.clone().unwrap_or_default(),
+        admin.post_code.clone().unwrap_or_default(),
+        admin.level.map(|l| l.to_string()).unwrap_or_default(),
+        admin.boundary.is_some().to_string(),
+        admin.uri.clone(),
+    ]
+}
+
+/// print the cities in the input order. The table is only printed by flush, its columns
+/// being as wide as their longest value
+pub struct ListWriter<W: Write> {
+    writer: BufWriter<W>,
+    format: ListFormat,
+    rows: Vec<[String; 7]>,
+    nb_written: usize,
+}
+
+impl<W: Write> ListWriter<W> {
+    pub fn new(writer: W, format: ListFormat) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            format,
+            rows: vec![],
+            nb_written: 0,
+        }
+    }
+
+    fn write_table(&mut self) -> Result<(), Error> {
+        let mut widths: Vec<usize> = COLUMNS.iter().map(|c| c.chars().count()).collect();
+        for row in &self.rows {
+            for (width, value) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(value.chars().count());
+            }
+        }
+        let header: Vec<_> = COLUMNS.iter().map(|c| c.to_string()).collect();
+        for row in std::iter::once(header.as_slice()).chain(self.rows.iter().map(|r| &r[..])) {
+            let line: Vec<_> = row
+                .iter()
+                .zip(&widths)
+                .map(|(value, width)| format!("{:<1$}", value, width))
+                .collect();
+            writeln!(self.writer, "{}", line.join(" | ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> OutputWriter for ListWriter<W> {
+    fn extension(&self) -> &'static str {
+        match self.format {
+            ListFormat::Table => "txt",
+            ListFormat::Json => "json",
+            ListFormat::Csv => "csv",
+        }
+    }
+
+    fn write(&mut self, admin: &AdministrativeRegion) -> Result<(), Error> {
+        match self.format {
+            ListFormat::Table => self.rows.push(row(admin)),
+            // a JSON array of the cities, serialized like the JSON lines
+            ListFormat::Json => {
+                self.writer
+                    .write_all(if self.nb_written == 0 { b"[\n" } else { b",\n" })?;
+                serde_json::to_writer(&mut self.writer, admin)?;
+            }
+            ListFormat::Csv => {
+                if self.nb_written == 0 {
+                    writeln!(self.writer, "{}", COLUMNS.join(","))?;
+                }
+                let fields: Vec<_> = row(admin)
+                    .iter()
+                    .map(|f| csv_field(f).into_owned())
+                    .collect();
+                writeln!(self.writer, "{}", fields.join(","))?;
+            }
+        }
+        self.nb_written += 1;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        match self.format {
+            ListFormat::Table => self.write_table()?,
+            ListFormat::Json if self.nb_written == 0 => self.writer.write_all(b"[]\n")?,
+            ListFormat::Json => self.writer.write_all(b"\n]\n")?,
+            ListFormat::Csv if self.nb_written == 0 => {
+                writeln!(self.writer, "{}", COLUMNS.join(","))?
+            }
+            ListFormat::Csv => {}
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn listed(format: ListFormat) -> String {
+        let admin = AdministrativeRegion::from(crate::test_utils::sample_zone());
+        let mut out = vec![];
+        {
+            let mut writer = ListWriter::new(&mut out, format);
+            writer.write(&admin).unwrap();
+            writer.flush().unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn list_formats() {
+        assert_eq!(
+            listed(ListFormat::Table),
+            "id | name                    | insee | post_code | level | has_boundary | uri\n\
+             0  | Saint-Denis, La Réunion | 97411 |           | 8     | true         | admin:fr:97411\n"
+        );
+        assert_eq!(
+            listed(ListFormat::Csv),
+            "id,name,insee,post_code,level,has_boundary,uri\n\
+             0,\"Saint-Denis, La Réunion\",97411,,8,true,admin:fr:97411\n"
+        );
+        let json: Vec<AdministrativeRegion> =
+            serde_json::from_str(&listed(ListFormat::Json)).unwrap();
+        assert_eq!(json.len(), 1);
+        assert_eq!(json[0].insee.as_deref(), Some("97411"));
+    }
+}
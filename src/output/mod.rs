@@ -0,0 +1,87 @@
+use crate::AdministrativeRegion;
+use failure::Error;
+use std::borrow::Cow;
+use std::io::{BufWriter, Write};
+
+pub mod es_bulk;
+pub mod list;
+pub mod split;
+
+/// destination of the converted cities, other than the database
+pub trait OutputWriter {
+    /// extension of the files written by this kind of writer
+    fn extension(&self) -> &'static str;
+    fn write(&mut self, admin: &AdministrativeRegion) -> Result<(), Error>;
+    fn flush(&mut self) -> Result<(), Error>;
+}
+
+/// write each admin as a JSON object on its own line
+pub struct JsonLinesWriter<W: Write> {
+    writer: BufWriter<W>,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+        }
+    }
+}
+
+impl<W: Write> OutputWriter for JsonLinesWriter<W> {
+    fn extension(&self) -> &'static str {
+        "jsonl"
+    }
+
+    fn write(&mut self, admin: &AdministrativeRegion) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.writer, admin)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// keep the cities in memory, to check the conversion without any database
+#[derive(Debug, Default)]
+pub struct VecSink {
+    pub admins: Vec<AdministrativeRegion>,
+}
+
+impl OutputWriter for VecSink {
+    /// nothing is written in a file, but the cities are JSON objects like the JSON lines
+    fn extension(&self) -> &'static str {
+        "jsonl"
+    }
+
+    fn write(&mut self, admin: &AdministrativeRegion) -> Result<(), Error> {
+        self.admins.push(admin.clone());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// the field quoted if it contains a separator, a quote or a line break
+pub(crate) fn csv_field(field: &str) -> Cow<str> {
+    if field.contains(|c: char| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+pub fn write_all(
+    admins: impl Iterator<Item = AdministrativeRegion>,
+    writer: &mut dyn OutputWriter,
+) -> Result<(), Error> {
+    for admin in admins {
+        writer.write(&admin)?;
+    }
+    writer.flush()
+}
@@ -0,0 +1,90 @@
+use super::{JsonLinesWriter, OutputWriter};
+use crate::AdministrativeRegion;
+use failure::Error;
+use log::info;
+use std::collections::btree_map::{BTreeMap, Entry};
+use std::fs::File;
+use std::path::PathBuf;
+
+/// département used for the cities without a French INSEE code
+const UNKNOWN_DEPARTMENT: &str = "unknown";
+
+/// French département of an INSEE code: its first 2 characters (2A and 2B for Corsica),
+/// or 3 for the overseas départements (971 to 989)
+pub fn department_code(insee: &str) -> Option<&str> {
+    if insee.len() != 5 || !insee.is_ascii() {
+        return None;
+    }
+    if insee.starts_with("97") || insee.starts_with("98") {
+        Some(&insee[..3])
+    } else {
+        Some(&insee[..2])
+    }
+}
+
+/// route each admin to the file of its département, named `{prefix}_{department_code}.{extension}`
+pub struct DepartmentRouter {
+    output_dir: PathBuf,
+    prefix: String,
+    writers: BTreeMap<String, Box<dyn OutputWriter>>,
+}
+
+impl DepartmentRouter {
+    pub fn new(output_dir: impl Into<PathBuf>, prefix: impl Into<String>) -> Self {
+        Self {
+            output_dir: output_dir.into(),
+            prefix: prefix.into(),
+            writers: BTreeMap::new(),
+        }
+    }
+}
+
+impl OutputWriter for DepartmentRouter {
+    fn extension(&self) -> &'static str {
+        "jsonl"
+    }
+
+    fn write(&mut self, admin: &AdministrativeRegion) -> Result<(), Error> {
+        let department = admin
+            .insee
+            .as_ref()
+            .and_then(|insee| department_code(insee))
+            .unwrap_or(UNKNOWN_DEPARTMENT);
+        let extension = self.extension();
+        let writer = match self.writers.entry(department.to_owned()) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let path = self
+                    .output_dir
+                    .join(format!("{}_{}.{}", self.prefix, department, extension));
+                info!(
+                    "writing the cities of département {} in {:?}",
+                    department, path
+                );
+                e.insert(Box::new(JsonLinesWriter::new(File::create(path)?)))
+            }
+        };
+        writer.write(admin)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        for writer in self.writers.values_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn department_codes() {
+        assert_eq!(department_code("75111"), Some("75"));
+        assert_eq!(department_code("01249"), Some("01"));
+        assert_eq!(department_code("2A004"), Some("2A"));
+        assert_eq!(department_code("97411"), Some("974"));
+        assert_eq!(department_code("bob"), None);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::output::csv_field;
+use failure::Error;
+use log::{info, warn};
+use postgres::Connection;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::Write;
+
+/// 2 cities whose boundaries overlap, found by --check-overlaps
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Overlap {
+    pub id: i64,
+    pub name: String,
+    pub insee: Option<String>,
+    pub other_id: i64,
+    pub other_name: String,
+    pub other_insee: Option<String>,
+    pub area_km2: f64,
+}
+
+/// the pairs of cities whose common area is larger than `min_area_km2`, the cities touching
+/// each other having a common area of 0. The candidates are found with the GIST index on
+/// boundary. With a `sample`, only the overlaps of this number of random cities are searched
+pub fn find_overlaps(
+    cnx: &Connection,
+    min_area_km2: f64,
+    sample: Option<u32>,
+) -> Result<Vec<Overlap>, Error> {
+    let (cities, pair_filter) = match sample {
+        Some(n) => (
+            format!(
+                "(SELECT * FROM administrative_regions WHERE boundary IS NOT NULL
+                ORDER BY random() LIMIT {})",
+                n
+            ),
+            "a.id <> b.id",
+        ),
+        None => ("administrative_regions".to_owned(), "a.id < b.id"),
+    };
+    let rows = cnx.query(
+        &format!(
+            "SELECT * FROM (
+                SELECT a.id, a.name, a.insee, b.id, b.name, b.insee,
+                    ST_Area(ST_Intersection(a.boundary, b.boundary)) / 1e6 AS area_km2
+                FROM {} a JOIN administrative_regions b
+                ON a.boundary && b.boundary AND ST_Intersects(a.boundary, b.boundary)
+                AND {}
+            ) overlaps
+            WHERE area_km2 > $1
+            ORDER BY area_km2 DESC;",
+            cities, pair_filter
+        ),
+        &[&min_area_km2],
+    )?;
+    // with a sample, the pairs of sampled cities are found twice
+    let mut pairs = HashSet::new();
+    Ok(rows
+        .iter()
+        .map(|row| Overlap {
+            id: row.get(0),
+            name: row.get(1),
+            insee: row.get(2),
+            other_id: row.get(3),
+            other_name: row.get(4),
+            other_insee: row.get(5),
+            area_km2: row.get(6),
+        })
+        .filter(|o| pairs.insert((o.id.min(o.other_id), o.id.max(o.other_id))))
+        .collect())
+}
+
+pub fn log_overlaps(overlaps: &[Overlap]) {
+    for o in overlaps {
+        warn!(
+            "{} ({}, INSEE {}) and {} ({}, INSEE {}) overlap on {:.3}km²",
+            o.name,
+            o.id,
+            o.insee.as_deref().unwrap_or("unknown"),
+            o.other_name,
+            o.other_id,
+            o.other_insee.as_deref().unwrap_or("unknown"),
+            o.area_km2
+        );
+    }
+    info!("{} pairs of overlapping cities", overlaps.len());
+}
+
+pub fn write_csv(overlaps: &[Overlap], mut out: impl Write) -> Result<(), Error> {
+    writeln!(
+        out,
+        "id,name,insee,other_id,other_name,other_insee,area_km2"
+    )?;
+    for o in overlaps {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{}",
+            o.id,
+            csv_field(&o.name),
+            csv_field(o.insee.as_deref().unwrap_or("")),
+            o.other_id,
+            csv_field(&o.other_name),
+            csv_field(o.other_insee.as_deref().unwrap_or("")),
+            o.area_km2
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn csv_report() {
+        let overlaps = vec![Overlap {
+            id: 1,
+            name: "Saint-Martin, \"le haut\"".to_owned(),
+            insee: Some("01001".to_owned()),
+            other_id: 2,
+            other_name: "Saint-Martin".to_owned(),
+            other_insee: None,
+            area_km2: 0.5,
+        }];
+        let mut out = vec![];
+        write_csv(&overlaps, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "id,name,insee,other_id,other_name,other_insee,area_km2\n\
+             1,\"Saint-Martin, \"\"le haut\"\"\",01001,2,Saint-Martin,,0.5\n"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use crate::names::OnEmptyName;
+use std::str::FromStr;
+
+/// what to do with a city whose required field has no valid value
+#[derive(Debug, Clone, PartialEq)]
+pub enum NullStrategy {
+    /// do not import the city
+    Skip,
+    /// import the city with this value instead
+    Default(String),
+    /// abort the import
+    Fail,
+    /// import the city with the invalid value, like --on-empty-name keep
+    Keep,
+}
+
+impl FromStr for NullStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(NullStrategy::Skip),
+            "fail" => Ok(NullStrategy::Fail),
+            "keep" => Ok(NullStrategy::Keep),
+            _ if s.starts_with("default:") && s.len() > "default:".len() => {
+                Ok(NullStrategy::Default(s["default:".len()..].to_owned()))
+            }
+            _ => Err(format!(
+                "invalid strategy '{}', it should be skip, fail, keep or default:<value>",
+                s
+            )),
+        }
+    }
+}
+
+impl From<OnEmptyName> for NullStrategy {
+    fn from(on_empty_name: OnEmptyName) -> Self {
+        match on_empty_name {
+            OnEmptyName::Skip => NullStrategy::Skip,
+            OnEmptyName::Keep => NullStrategy::Keep,
+            OnEmptyName::Fail => NullStrategy::Fail,
+        }
+    }
+}
+
+/// the strategies of the required fields of the cities, the name and the uri being
+/// NOT NULL in administrative_regions
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldPolicy {
+    pub name: NullStrategy,
+    pub uri: NullStrategy,
+}
+
+/// false if the uri has no identifier after its prefix, like admin:osm: for a zone
+/// without osm id
+pub fn is_valid_uri(uri: &str) -> bool {
+    !uri.trim().is_empty() && !uri.ends_with(':')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_strategies() {
+        assert_eq!("skip".parse(), Ok(NullStrategy::Skip));
+        assert_eq!("fail".parse(), Ok(NullStrategy::Fail));
+        assert_eq!(
+            "default:admin:unknown".parse(),
+            Ok(NullStrategy::Default("admin:unknown".to_owned()))
+        );
+        assert!("default:".parse::<NullStrategy>().is_err());
+        assert!("ignore".parse::<NullStrategy>().is_err());
+    }
+
+    #[test]
+    fn valid_uris() {
+        assert!(is_valid_uri("admin:fr:75111"));
+        assert!(!is_valid_uri("admin:osm:"));
+        assert!(!is_valid_uri(""));
+    }
+}
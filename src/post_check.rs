@@ -0,0 +1,171 @@
+use failure::Error;
+use log::{info, warn};
+use postgres::{rows::Rows, Connection};
+use std::fmt;
+
+/// a problem of a city found by --post-check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Problem {
+    /// the boundary is not valid for PostGIS, like a self-intersecting ring
+    InvalidGeometry,
+    NullBoundary,
+    ZeroArea,
+    /// the boundary_geom of --dual-geometry cannot be cast to a geography: it is not in
+    /// WGS84 or its coordinates are out of range
+    NotGeography,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Problem::InvalidGeometry => "invalid geometry",
+            Problem::NullBoundary => "no boundary",
+            Problem::ZeroArea => "boundary of zero area",
+            Problem::NotGeography => "boundary_geom not castable to geography",
+        })
+    }
+}
+
+/// a city failing the checks
+#[derive(Debug, PartialEq)]
+pub struct Offender {
+    pub id: i64,
+    pub name: String,
+    pub problems: Vec<Problem>,
+}
+
+/// a city whose center is not covered by its boundary
+#[derive(Debug, PartialEq)]
+pub struct CenterOutside {
+    pub id: i64,
+    pub name: String,
+    /// distance from the center to the boundary, in meters
+    pub distance_m: f64,
+}
+
+/// run the query in a read only transaction
+fn read_only_query(cnx: &Connection, query: &str) -> Result<Rows, Error> {
+    let transaction = cnx.transaction()?;
+    transaction.batch_execute("SET TRANSACTION READ ONLY;")?;
+    let rows = transaction.query(query, &[])?;
+    transaction.commit()?;
+    Ok(rows)
+}
+
+fn has_column(cnx: &Connection, column: &str) -> Result<bool, Error> {
+    let rows = cnx.query(
+        "SELECT 1 FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = 'administrative_regions'
+        AND column_name = $1;",
+        &[&column],
+    )?;
+    Ok(!rows.is_empty())
+}
+
+/// the cities of administrative_regions whose boundary is not usable, found in a single read
+/// only scan of the table
+pub fn offenders(cnx: &Connection) -> Result<Vec<Offender>, Error> {
+    let not_geography = if has_column(cnx, "boundary_geom")? {
+        "coalesce(ST_SRID(boundary_geom) <> 4326
+            OR ST_XMin(boundary_geom) < -180 OR ST_XMax(boundary_geom) > 180
+            OR ST_YMin(boundary_geom) < -90 OR ST_YMax(boundary_geom) > 90, false)"
+    } else {
+        "false"
+    };
+    let rows = read_only_query(
+        cnx,
+        &format!(
+            "SELECT id, name, invalid, null_boundary, zero_area, not_geography FROM (
+                SELECT id, name,
+                    coalesce(NOT ST_IsValid(boundary::geometry), false) AS invalid,
+                    boundary IS NULL AS null_boundary,
+                    coalesce(ST_Area(boundary::geometry) = 0, false) AS zero_area,
+                    {} AS not_geography
+                FROM administrative_regions
+            ) checks
+            WHERE invalid OR null_boundary OR zero_area OR not_geography
+            ORDER BY id;",
+            not_geography
+        ),
+    )?;
+    let offenders = rows
+        .iter()
+        .map(|row| {
+            let problems = [
+                Problem::InvalidGeometry,
+                Problem::NullBoundary,
+                Problem::ZeroArea,
+                Problem::NotGeography,
+            ]
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| row.get::<_, bool>(i + 2))
+            .map(|(_, problem)| *problem)
+            .collect();
+            Offender {
+                id: row.get(0),
+                name: row.get(1),
+                problems,
+            }
+        })
+        .collect();
+    Ok(offenders)
+}
+
+/// the cities whose center is outside of their boundary
+pub fn centers_outside(cnx: &Connection) -> Result<Vec<CenterOutside>, Error> {
+    let rows = read_only_query(
+        cnx,
+        "SELECT id, name, ST_Distance(boundary, coord) FROM administrative_regions
+        WHERE coord IS NOT NULL AND boundary IS NOT NULL AND NOT ST_Covers(boundary, coord)
+        ORDER BY id;",
+    )?;
+    Ok(rows
+        .iter()
+        .map(|row| CenterOutside {
+            id: row.get(0),
+            name: row.get(1),
+            distance_m: row.get(2),
+        })
+        .collect())
+}
+
+/// log the cities whose center is outside of their boundary, and return their number
+pub fn check_centers(cnx: &Connection) -> Result<usize, Error> {
+    let outside = centers_outside(cnx)?;
+    for city in &outside {
+        warn!(
+            "city {} ({}): center outside of the boundary, {:.0}m away",
+            city.name, city.id, city.distance_m
+        );
+    }
+    Ok(outside.len())
+}
+
+/// log the cities failing the checks, and return the number of them that are errors. The
+/// cities without boundary are only errors when they are not expected
+pub fn check(cnx: &Connection, null_boundary_allowed: bool) -> Result<usize, Error> {
+    let (mut nb_errors, mut nb_without_boundary) = (0, 0);
+    for offender in offenders(cnx)? {
+        if offender.problems.contains(&Problem::NullBoundary) {
+            nb_without_boundary += 1;
+        }
+        let problems: Vec<_> = offender
+            .problems
+            .iter()
+            .filter(|p| !null_boundary_allowed || **p != Problem::NullBoundary)
+            .map(Problem::to_string)
+            .collect();
+        if !problems.is_empty() {
+            warn!(
+                "city {} ({}): {}",
+                offender.name,
+                offender.id,
+                problems.join(", ")
+            );
+            nb_errors += 1;
+        }
+    }
+    info!("{} cities without boundary", nb_without_boundary);
+    Ok(nb_errors)
+}
@@ -0,0 +1,157 @@
+use log::info;
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// counters of the pipeline, shared by the progress logs and the final summary
+pub static ZONES_READ: AtomicUsize = AtomicUsize::new(0);
+pub static CITIES_CONVERTED: AtomicUsize = AtomicUsize::new(0);
+pub static ROWS_INSERTED: AtomicUsize = AtomicUsize::new(0);
+pub static NO_BOUNDARY_EXCLUDED: AtomicUsize = AtomicUsize::new(0);
+pub static SMALL_AREA_EXCLUDED: AtomicUsize = AtomicUsize::new(0);
+pub static INVALID_GEOMETRY_EXCLUDED: AtomicUsize = AtomicUsize::new(0);
+/// bytes of the input file read, and its size, 0 when the input is not a file
+pub static BYTES_READ: AtomicUsize = AtomicUsize::new(0);
+pub static INPUT_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+pub fn incr(counter: &AtomicUsize, nb: usize) {
+    counter.fetch_add(nb, Ordering::Relaxed);
+}
+
+pub fn get(counter: &AtomicUsize) -> usize {
+    counter.load(Ordering::Relaxed)
+}
+
+/// set all the counters back to 0, before the zones are read again
+pub fn reset() {
+    for counter in &[
+        &ZONES_READ,
+        &CITIES_CONVERTED,
+        &ROWS_INSERTED,
+        &NO_BOUNDARY_EXCLUDED,
+        &SMALL_AREA_EXCLUDED,
+        &INVALID_GEOMETRY_EXCLUDED,
+        &BYTES_READ,
+    ] {
+        counter.store(0, Ordering::Relaxed);
+    }
+}
+
+pub fn log_counters() {
+    info!(
+        "read {} zones, converted {} cities, inserted {} rows",
+        get(&ZONES_READ),
+        get(&CITIES_CONVERTED),
+        get(&ROWS_INSERTED)
+    );
+}
+
+/// number of cities excluded by each boundary filter
+pub fn log_excluded() {
+    for (counter, filter) in &[
+        (&NO_BOUNDARY_EXCLUDED, "without boundary"),
+        (
+            &SMALL_AREA_EXCLUDED,
+            "with a boundary smaller than --boundary-min-area-km2",
+        ),
+        (&INVALID_GEOMETRY_EXCLUDED, "with an invalid boundary"),
+    ] {
+        if get(counter) > 0 {
+            info!("excluded {} cities {}", get(counter), filter);
+        }
+    }
+}
+
+/// reader counting the bytes read from the input file in BYTES_READ
+pub struct CountingReader<R> {
+    inner: R,
+}
+
+impl<R> CountingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let nb = self.inner.read(buf)?;
+        incr(&BYTES_READ, nb);
+        Ok(nb)
+    }
+}
+
+/// share of the input file read, with the time left to read the rest at the rate of the
+/// last tick
+fn log_input_progress(bytes_read: usize, bytes_per_sec: f64) {
+    let size = get(&INPUT_SIZE);
+    if size == 0 {
+        return;
+    }
+    let left = size.saturating_sub(bytes_read);
+    let eta = if left == 0 {
+        "done".to_owned()
+    } else if bytes_per_sec > 0. {
+        let eta = Duration::from_secs((left as f64 / bytes_per_sec).ceil() as u64);
+        humantime::format_duration(eta).to_string()
+    } else {
+        "unknown".to_owned()
+    };
+    info!(
+        "read {:.1}/{:.1}MB of the input ({:.0}%), ETA {}",
+        bytes_read as f64 / 1024. / 1024.,
+        size as f64 / 1024. / 1024.,
+        100. * bytes_read as f64 / size as f64,
+        eta
+    );
+}
+
+/// thread logging the counters at a regular interval, stopped when dropped
+pub struct Ticker {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Ticker {
+    pub fn start(interval: Duration) -> Self {
+        let (stop, stopped) = channel();
+        let handle = std::thread::spawn(move || {
+            let mut last_tick = Instant::now();
+            let mut last_inserted = get(&ROWS_INSERTED);
+            let mut last_bytes_read = get(&BYTES_READ);
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                let inserted = get(&ROWS_INSERTED);
+                let bytes_read = get(&BYTES_READ);
+                let elapsed = last_tick.elapsed().as_secs_f64();
+                // the throughput is the one since the last tick, not since the beginning. The
+                // counters are reset when the import is retried, the rows of the previous tick
+                // being then in the rolled back attempt
+                let rate = inserted.saturating_sub(last_inserted) as f64 / elapsed;
+                log_counters();
+                log_input_progress(
+                    bytes_read,
+                    bytes_read.saturating_sub(last_bytes_read) as f64 / elapsed,
+                );
+                info!("inserting {:.0} rows/s", rate);
+                last_tick = Instant::now();
+                last_inserted = inserted;
+                last_bytes_read = bytes_read;
+            }
+        });
+        Self {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{multipolygon_from_wkt, point_from_wkt, AdministrativeRegion, SRID};
+use failure::Error;
+use postgres::{rows::Row, Connection};
+
+const SELECT_REGIONS: &str = "SELECT id, name, uri, post_code, insee, level,
+    ST_AsText(coord), ST_AsText(boundary), zone_hash_ring, alt_uris, osm_relation_id,
+    source_md5, boundary_polygon_count, is_valid_geometry, ST_AsText(osm_center),
+    ST_AsText(computed_centroid), density_classification, coastal_distance_km,
+    parent_id, is_regional_capital, is_departement_capital, is_multi_part, component_osm_ids,
+    utc_offset_minutes, name_without_prefix, postal_code_array,
+    boundary_ring_count, hole_count, has_self_intersection
+    FROM administrative_regions";
+
+fn region_from_row(row: &Row) -> Result<AdministrativeRegion, Error> {
+    let point = |i: usize| {
+        row.get::<_, Option<String>>(i)
+            .map(|wkt| point_from_wkt(&wkt))
+            .transpose()
+    };
+    let boundary = row
+        .get::<_, Option<String>>(7)
+        .map(|wkt| multipolygon_from_wkt(&wkt))
+        .transpose()?;
+    Ok(AdministrativeRegion {
+        id: row.get(0),
+        name: row.get(1),
+        uri: row.get(2),
+        post_code: row.get(3),
+        // only the range of the zip codes is stored in the table
+        zip_codes: vec![],
+        insee: row.get(4),
+        level: row.get(5),
+        coord: point(6)?,
+        osm_center: point(14)?,
+        computed_centroid: point(15)?,
+        boundary,
+        simplified_boundary: None,
+        boundary_polygon_count: row.get::<_, Option<i32>>(12).map(|c| c as u32),
+        is_valid_geometry: row.get(13),
+        density_classification: row.get(16),
+        coastal_distance_km: row.get::<_, Option<f32>>(17).map(f64::from),
+        parent_id: row.get(18),
+        is_regional_capital: row.get(19),
+        is_departement_capital: row.get(20),
+        is_multi_part: row.get::<_, Option<bool>>(21).unwrap_or_default(),
+        component_osm_ids: row.get(22),
+        // only the offset is stored in the table
+        timezone: None,
+        utc_offset_minutes: row.get(23),
+        name_without_prefix: row.get(24),
+        postal_code_array: row.get::<_, Option<Vec<String>>>(25).unwrap_or_default(),
+        boundary_ring_count: row.get::<_, Option<i32>>(26).map(|c| c as u32),
+        hole_count: row.get::<_, Option<i32>>(27).map(|c| c as u32),
+        has_self_intersection: row.get(28),
+        zone_hash_ring: row.get(8),
+        alt_uris: row.get(9),
+        osm_relation_id: row.get(10),
+        source_md5: row.get::<_, Option<String>>(11).unwrap_or_default(),
+        warnings: vec![],
+    })
+}
+
+/// the imported cities whose boundary overlaps the bounding box, ordered by level and name.
+/// The `&&` operator only compares the bounding boxes, so it uses the GIST index on boundary
+pub fn lookup_zones_in_bbox(
+    cnx: &Connection,
+    min_lon: f64,
+    min_lat: f64,
+    max_lon: f64,
+    max_lat: f64,
+) -> Result<Vec<AdministrativeRegion>, Error> {
+    let query = format!(
+        "{} WHERE boundary && ST_MakeEnvelope($1, $2, $3, $4, {})::geography
+        ORDER BY level ASC, name ASC",
+        SELECT_REGIONS, SRID
+    );
+    cnx.query(&query, &[&min_lon, &min_lat, &max_lon, &max_lat])?
+        .iter()
+        .map(|row| region_from_row(&row))
+        .collect()
+}
+
+/// the imported city with this INSEE code.
+/// The table should have an index on insee (see administrative_regions_insee_idx in schema.sql)
+pub fn lookup_zone_by_insee(
+    cnx: &Connection,
+    insee: &str,
+) -> Result<Option<AdministrativeRegion>, Error> {
+    let query = format!("{} WHERE insee = $1 LIMIT 1", SELECT_REGIONS);
+    cnx.query(&query, &[&insee])?
+        .iter()
+        .next()
+        .map(|row| region_from_row(&row))
+        .transpose()
+}
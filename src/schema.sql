@@ -0,0 +1,97 @@
+CREATE TABLE administrative_regions (
+    id BIGINT PRIMARY KEY,
+    name TEXT NOT NULL,
+    uri TEXT NOT NULL,
+    post_code TEXT,
+    insee TEXT,
+    level integer,
+    coord geography(Point,4326),
+    boundary geography(MultiPolygon,4326),
+    zone_hash_ring SMALLINT NOT NULL DEFAULT 0,
+    alt_uris TEXT[] NOT NULL DEFAULT '{}',
+    osm_relation_id BIGINT,
+    source_md5 CHAR(32),
+    envelope_wkt TEXT,
+    boundary_polygon_count INT,
+    -- number of rings of the boundary, and of its interior rings only
+    boundary_ring_count INT,
+    hole_count INT,
+    is_valid_geometry BOOLEAN,
+    -- boundary crossing itself, filled with --validate-geometry
+    has_self_intersection BOOLEAN NOT NULL DEFAULT FALSE,
+    -- point inside the boundary, filled by --compute-interior-points
+    interior_point geography(Point,4326),
+    -- center given by OSM, and centroid of the boundary. The coord is the first one,
+    -- or the second one when OSM gives no center. With PostgreSQL 12+, it can be:
+    -- coord geography(Point,4326) GENERATED ALWAYS AS (COALESCE(osm_center, computed_centroid)) STORED
+    osm_center geography(Point,4326),
+    computed_centroid geography(Point,4326),
+    -- from the population tag and the area of the boundary: < 30/km², up to 500/km², above
+    density_classification TEXT CHECK (density_classification IN ('rural','semi-urban','urban')),
+    -- distance to the coast, filled with --coastal-coastline-file
+    coastal_distance_km REAL,
+    -- commune of the arrondissements of Paris, Lyon and Marseille, filled with
+    -- --arrondissements parent-link
+    parent_id BIGINT,
+    -- from the capital tag: 3 or 4 for the regions, 6 for the départements
+    is_regional_capital BOOLEAN NOT NULL DEFAULT FALSE,
+    is_departement_capital BOOLEAN NOT NULL DEFAULT FALSE,
+    -- zones merging several former communes, with the references of these communes
+    is_multi_part BOOLEAN,
+    component_osm_ids TEXT[],
+    -- UTC offset of the timezone tag when imported, not updated at the daylight saving time changes
+    utc_offset_minutes SMALLINT,
+    -- the name without its prefix like Saint- or Le
+    name_without_prefix TEXT NOT NULL DEFAULT '',
+    -- all the zip codes, post_code being only their range
+    postal_code_array TEXT[]
+    -- with PostgreSQL 12+, the envelope can also be stored as a geography with:
+    -- envelope geography(Polygon,4326) GENERATED ALWAYS AS (ST_Envelope(boundary::geometry)::geography) STORED
+);
+
+CREATE INDEX administrative_regions_boundary_idx ON administrative_regions USING gist (boundary);
+CREATE INDEX administrative_regions_zone_hash_ring_idx ON administrative_regions (zone_hash_ring);
+CREATE UNIQUE INDEX administrative_regions_osm_relation_id_idx ON administrative_regions (osm_relation_id);
+CREATE INDEX administrative_regions_insee_idx ON administrative_regions (insee);
+-- for the membership queries like postal_code_array @> ARRAY['75011'], which can use it unlike
+-- '75011' = ANY(postal_code_array)
+CREATE INDEX administrative_regions_postal_code_array_idx ON administrative_regions USING gin (postal_code_array);
+-- text_pattern_ops for the prefix searches like name_without_prefix LIKE 'Martin%'
+CREATE INDEX administrative_regions_name_without_prefix_idx ON administrative_regions (name_without_prefix text_pattern_ops);
+CREATE INDEX administrative_regions_urban_boundary_idx ON administrative_regions USING gist (boundary)
+    WHERE density_classification = 'urban';
+
+-- geometry copy of the boundary, for --dual-geometry:
+-- ALTER TABLE administrative_regions ADD COLUMN boundary_geom geometry(MultiPolygon,4326);
+-- CREATE INDEX administrative_regions_boundary_geom_idx ON administrative_regions USING gist (boundary_geom);
+
+-- column of the simplified boundaries, for --simplified-column boundary_simplified:
+-- ALTER TABLE administrative_regions ADD COLUMN boundary_simplified geography(MultiPolygon,4326);
+-- CREATE INDEX administrative_regions_boundary_simplified_idx ON administrative_regions USING gist (boundary_simplified);
+
+-- full-text search column added by --fts (with PostgreSQL 12+, older versions using a trigger):
+-- ALTER TABLE administrative_regions ADD COLUMN fts_vector TSVECTOR
+--     GENERATED ALWAYS AS (to_tsvector('french', coalesce(name, ''))) STORED;
+-- CREATE INDEX administrative_regions_fts_vector_idx ON administrative_regions USING gin (fts_vector);
+
+-- generations of administrative_regions kept by --versioned, created by the first versioned import:
+-- CREATE TABLE administrative_regions_generations (
+--     id BIGSERIAL PRIMARY KEY,
+--     table_name TEXT NOT NULL UNIQUE,
+--     imported_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+--     source_md5 TEXT,
+--     nb_cities BIGINT NOT NULL,
+--     active BOOLEAN NOT NULL DEFAULT FALSE
+-- );
+
+CREATE TABLE non_administrative_regions (
+    id BIGINT PRIMARY KEY,
+    name TEXT NOT NULL,
+    uri TEXT NOT NULL,
+    coord geography(Point,4326),
+    boundary geography(MultiPolygon,4326),
+    osm_relation_id BIGINT,
+    category TEXT
+);
+
+CREATE INDEX non_administrative_regions_boundary_idx ON non_administrative_regions USING gist (boundary);
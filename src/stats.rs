@@ -0,0 +1,295 @@
+use crate::budget::SkipReason;
+use crate::logger::highlight;
+use cosmogony::{Zone, ZoneType};
+use failure::Error;
+use log::info;
+use serde::{Serialize, Serializer};
+use std::collections::HashMap;
+use std::fs::File;
+use std::time::Duration;
+
+/// summary of the zones read for one zone type
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ZoneTypeStats {
+    pub zone_type: Option<ZoneType>,
+    pub count: u64,
+    pub with_geometry: u64,
+    pub with_insee: u64,
+    pub with_postcode: u64,
+}
+
+impl ZoneTypeStats {
+    fn new(zone_type: Option<ZoneType>) -> Self {
+        Self {
+            zone_type,
+            count: 0,
+            with_geometry: 0,
+            with_insee: 0,
+            with_postcode: 0,
+        }
+    }
+}
+
+/// count the zone in the stats of its zone type
+pub fn add_zone(stats: &mut Vec<ZoneTypeStats>, zone: &Zone) {
+    let idx = match stats.iter().position(|s| s.zone_type == zone.zone_type) {
+        Some(idx) => idx,
+        None => {
+            stats.push(ZoneTypeStats::new(zone.zone_type));
+            stats.len() - 1
+        }
+    };
+    let type_stats = &mut stats[idx];
+    type_stats.count += 1;
+    if zone.boundary.is_some() {
+        type_stats.with_geometry += 1;
+    }
+    if zone.tags.get("ref:INSEE").is_some() {
+        type_stats.with_insee += 1;
+    }
+    if zone.tags.get("addr:postcode").is_some() || zone.tags.get("postal_code").is_some() {
+        type_stats.with_postcode += 1;
+    }
+}
+
+pub fn log_table(stats: &[ZoneTypeStats]) {
+    info!(
+        "{:<20} | {:>10} | {:>13} | {:>10} | {:>13}",
+        "zone type", "count", "with geometry", "with insee", "with postcode"
+    );
+    for s in stats {
+        let zone_type = s
+            .zone_type
+            .map_or_else(|| "none".to_owned(), |t| format!("{:?}", t));
+        info!(
+            "{:<20} | {:>10} | {:>13} | {:>10} | {:>13}",
+            zone_type, s.count, s.with_geometry, s.with_insee, s.with_postcode
+        );
+    }
+}
+
+pub fn write_json(stats: &[ZoneTypeStats], path: &str) -> Result<(), Error> {
+    serde_json::to_writer_pretty(File::create(path)?, stats)?;
+    info!("zone type stats written in {}", path);
+    Ok(())
+}
+
+fn serialize_as_secs<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+/// time spent in each phase of an import, serialized in seconds
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct PhaseDurations {
+    /// checks of the database before the import
+    #[serde(serialize_with = "serialize_as_secs")]
+    pub preflight: Duration,
+    /// conversion of the zones and insertion of the rows, done together
+    #[serde(serialize_with = "serialize_as_secs")]
+    pub insert: Duration,
+    #[serde(serialize_with = "serialize_as_secs")]
+    pub verify: Duration,
+    #[serde(serialize_with = "serialize_as_secs")]
+    pub non_administrative: Duration,
+    /// creation of the indexes of --create-indexes
+    #[serde(serialize_with = "serialize_as_secs")]
+    pub indexes: Duration,
+    /// computation of the interior points of --compute-interior-points
+    #[serde(serialize_with = "serialize_as_secs")]
+    pub interior_points: Duration,
+    #[serde(serialize_with = "serialize_as_secs")]
+    pub cluster: Duration,
+    /// search of the overlaps of --check-overlaps
+    #[serde(serialize_with = "serialize_as_secs")]
+    pub overlaps: Duration,
+    /// checks of --post-check
+    #[serde(serialize_with = "serialize_as_secs")]
+    pub post_check: Duration,
+}
+
+impl PhaseDurations {
+    fn merge(&mut self, other: PhaseDurations) {
+        self.preflight += other.preflight;
+        self.insert += other.insert;
+        self.verify += other.verify;
+        self.non_administrative += other.non_administrative;
+        self.indexes += other.indexes;
+        self.interior_points += other.interior_points;
+        self.cluster += other.cluster;
+        self.overlaps += other.overlaps;
+        self.post_check += other.post_check;
+    }
+}
+
+/// what an import did, each stage of the pipeline filling its part
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct ImportStats {
+    pub zones_read: u64,
+    pub cities_converted: u64,
+    pub rows_inserted: u64,
+    /// number of INSERT statements
+    pub chunks: u64,
+    /// number of rows above --large-row-threshold, inserted in their own statement
+    pub large_rows: u64,
+    pub skipped_by_reason: HashMap<SkipReason, u64>,
+    /// range of the levels in administrative_regions after the import
+    pub min_admin_level: Option<i32>,
+    pub max_admin_level: Option<i32>,
+    /// number of distinct levels in administrative_regions after the import
+    pub nb_admin_levels: u64,
+    /// the overlapping cities found by --check-overlaps
+    pub overlaps: Vec<crate::overlaps::Overlap>,
+    /// number of cities whose coord is outside of their boundary, for --post-check-centers
+    pub centers_outside_boundary: u64,
+    pub durations: PhaseDurations,
+}
+
+impl ImportStats {
+    pub fn skip(&mut self, reason: SkipReason, nb: u64) {
+        *self.skipped_by_reason.entry(reason).or_insert(0) += nb;
+    }
+
+    /// add the stats of another stage of the import
+    pub fn merge(&mut self, other: ImportStats) {
+        self.zones_read += other.zones_read;
+        self.cities_converted += other.cities_converted;
+        self.rows_inserted += other.rows_inserted;
+        self.chunks += other.chunks;
+        self.large_rows += other.large_rows;
+        for (reason, nb) in other.skipped_by_reason {
+            self.skip(reason, nb);
+        }
+        self.min_admin_level = self
+            .min_admin_level
+            .into_iter()
+            .chain(other.min_admin_level)
+            .min();
+        self.max_admin_level = self
+            .max_admin_level
+            .into_iter()
+            .chain(other.max_admin_level)
+            .max();
+        self.nb_admin_levels = self.nb_admin_levels.max(other.nb_admin_levels);
+        self.overlaps.extend(other.overlaps);
+        self.centers_outside_boundary += other.centers_outside_boundary;
+        self.durations.merge(other.durations);
+    }
+
+    pub fn log(&self) {
+        info!(
+            "{} zones read, {} cities converted, {} rows inserted in {} chunks ({} large rows)",
+            highlight(self.zones_read),
+            highlight(self.cities_converted),
+            highlight(self.rows_inserted),
+            highlight(self.chunks),
+            highlight(self.large_rows)
+        );
+        let mut skipped: Vec<_> = self.skipped_by_reason.iter().collect();
+        skipped.sort();
+        for (reason, nb) in skipped {
+            info!("{} zones skipped: {:?}", highlight(nb), reason);
+        }
+        if let (Some(min), Some(max)) = (self.min_admin_level, self.max_admin_level) {
+            info!(
+                "administrative levels from {} to {}, {} distinct levels",
+                highlight(min),
+                highlight(max),
+                highlight(self.nb_admin_levels)
+            );
+        }
+        if self.centers_outside_boundary > 0 {
+            info!(
+                "{} cities with their coord outside of their boundary",
+                highlight(self.centers_outside_boundary)
+            );
+        }
+        let d = &self.durations;
+        info!(
+            "preflight {:.1}s, insert {:.1}s, verify {:.1}s, non administrative zones {:.1}s, indexes {:.1}s, interior points {:.1}s, cluster {:.1}s, overlaps {:.1}s, post check {:.1}s",
+            d.preflight.as_secs_f64(),
+            d.insert.as_secs_f64(),
+            d.verify.as_secs_f64(),
+            d.non_administrative.as_secs_f64(),
+            d.indexes.as_secs_f64(),
+            d.interior_points.as_secs_f64(),
+            d.cluster.as_secs_f64(),
+            d.overlaps.as_secs_f64(),
+            d.post_check.as_secs_f64()
+        );
+    }
+
+    pub fn write_json(&self, path: &str) -> Result<(), Error> {
+        serde_json::to_writer_pretty(File::create(path)?, self)?;
+        info!("import summary written in {}", path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stats_by_zone_type() {
+        let mut city = Zone::default();
+        city.zone_type = Some(ZoneType::City);
+        city.tags = vec![("ref:INSEE", "75111"), ("postal_code", "75011")]
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v.to_owned()))
+            .collect();
+        let mut suburb = Zone::default();
+        suburb.zone_type = Some(ZoneType::Suburb);
+
+        let mut stats = vec![];
+        add_zone(&mut stats, &city);
+        add_zone(&mut stats, &suburb);
+        add_zone(&mut stats, &city);
+
+        assert_eq!(
+            stats,
+            vec![
+                ZoneTypeStats {
+                    zone_type: Some(ZoneType::City),
+                    count: 2,
+                    with_geometry: 0,
+                    with_insee: 2,
+                    with_postcode: 2,
+                },
+                ZoneTypeStats {
+                    zone_type: Some(ZoneType::Suburb),
+                    count: 1,
+                    with_geometry: 0,
+                    with_insee: 0,
+                    with_postcode: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn merged_import_stats() {
+        let mut stats = ImportStats {
+            zones_read: 3,
+            cities_converted: 2,
+            ..Default::default()
+        };
+        stats.skip(SkipReason::NotACity, 1);
+        let mut inserted = ImportStats {
+            rows_inserted: 2,
+            chunks: 2,
+            large_rows: 1,
+            ..Default::default()
+        };
+        inserted.skip(SkipReason::NotACity, 2);
+        inserted.durations.insert = Duration::from_millis(1500);
+        stats.merge(inserted);
+
+        assert_eq!(stats.zones_read, 3);
+        assert_eq!(stats.rows_inserted, 2);
+        assert_eq!(stats.large_rows, 1);
+        assert_eq!(stats.skipped_by_reason[&SkipReason::NotACity], 3);
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["skipped_by_reason"]["not_a_city"], 3);
+        assert_eq!(json["durations"]["insert"], 1.5);
+    }
+}
@@ -0,0 +1,24 @@
+//! fixtures shared by the unit tests
+
+use geo_types::{MultiPolygon, Polygon};
+use std::path::PathBuf;
+
+/// a city with an INSEE code and a triangle as boundary
+pub fn sample_zone() -> cosmogony::Zone {
+    let mut zone = cosmogony::Zone::default();
+    zone.name = "Saint-Denis, La Réunion".to_owned();
+    zone.osm_id = "r1".to_owned();
+    zone.tags = vec![("ref:INSEE".to_owned(), "97411".to_owned())]
+        .into_iter()
+        .collect();
+    zone.boundary = Some(MultiPolygon(vec![Polygon::new(
+        vec![(0., 0.), (1., 0.), (1., 1.), (0., 0.)].into(),
+        vec![],
+    )]));
+    zone
+}
+
+/// file of the temporary directory, unique to the test process
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("cosmogony2cities_{}_{}", std::process::id(), name))
+}
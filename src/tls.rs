@@ -0,0 +1,242 @@
+use failure::{format_err, Error};
+use postgres::tls::openssl::openssl::ssl::{
+    SslConnectorBuilder, SslMethod, SSL_VERIFY_NONE, SSL_VERIFY_PEER,
+};
+use postgres::tls::openssl::openssl::x509::X509_FILETYPE_PEM;
+use postgres::tls::openssl::OpenSsl;
+use postgres::TlsMode;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+/// the sslmode of libpq
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SslMode {
+    Disable,
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+impl FromStr for SslMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disable" => Ok(SslMode::Disable),
+            "prefer" => Ok(SslMode::Prefer),
+            "require" => Ok(SslMode::Require),
+            "verify-ca" => Ok(SslMode::VerifyCa),
+            "verify-full" => Ok(SslMode::VerifyFull),
+            _ => Err(format!(
+                "invalid sslmode '{}', it should be disable, prefer, require, verify-ca or verify-full",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SslMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+            SslMode::VerifyCa => "verify-ca",
+            SslMode::VerifyFull => "verify-full",
+        })
+    }
+}
+
+/// what is checked by a sslmode
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Verification {
+    /// fail if the server does not support TLS
+    required: bool,
+    /// the server certificate must chain to a trusted CA
+    certificate: bool,
+    /// the server certificate must be the one of the host
+    hostname: bool,
+}
+
+/// the checks of a sslmode, None if TLS is disabled. Like libpq, require behaves
+/// like verify-ca when a root certificate is given
+fn verification(mode: SslMode, has_root_cert: bool) -> Option<Verification> {
+    let (required, certificate, hostname) = match mode {
+        SslMode::Disable => return None,
+        SslMode::Prefer => (false, false, false),
+        SslMode::Require => (true, has_root_cert, false),
+        SslMode::VerifyCa => (true, true, false),
+        SslMode::VerifyFull => (true, true, true),
+    };
+    Some(Verification {
+        required,
+        certificate,
+        hostname,
+    })
+}
+
+#[derive(StructOpt, Debug)]
+pub struct TlsOptions {
+    /// sslmode of the connection: disable, prefer, require, verify-ca or verify-full.
+    /// It overrides the sslmode of the connection string, disable by default
+    #[structopt(long = "sslmode")]
+    sslmode: Option<SslMode>,
+
+    /// CA certificates (PEM) the server certificate must chain to, the system ones by default
+    #[structopt(long = "ssl-root-cert")]
+    ssl_root_cert: Option<String>,
+
+    /// client certificate (PEM), for the servers requiring one
+    #[structopt(long = "ssl-cert")]
+    ssl_cert: Option<String>,
+
+    /// private key (PEM) of --ssl-cert
+    #[structopt(long = "ssl-key")]
+    ssl_key: Option<String>,
+}
+
+/// TLS set up of a connection, from the options and the sslmode, sslrootcert, sslcert and
+/// sslkey of the connection string, the options winning
+pub struct Tls {
+    pub mode: SslMode,
+    handshake: Option<(OpenSsl, bool)>,
+}
+
+impl Tls {
+    pub fn new(options: &TlsOptions, ssl_options: &HashMap<String, String>) -> Result<Self, Error> {
+        let option = |value: &Option<String>, name: &str| {
+            value.clone().or_else(|| ssl_options.get(name).cloned())
+        };
+        let mode = match (options.sslmode, ssl_options.get("sslmode")) {
+            (Some(mode), _) => mode,
+            (None, Some(mode)) => mode.parse().map_err(|e: String| format_err!("{}", e))?,
+            (None, None) => SslMode::Disable,
+        };
+        let root_cert = option(&options.ssl_root_cert, "sslrootcert");
+        let verification = match verification(mode, root_cert.is_some()) {
+            Some(verification) => verification,
+            None => {
+                return Ok(Self {
+                    mode,
+                    handshake: None,
+                })
+            }
+        };
+        let tls_err = |e| format_err!("impossible to set up TLS with sslmode {}: {}", mode, e);
+        let mut builder = SslConnectorBuilder::new(SslMethod::tls()).map_err(tls_err)?;
+        {
+            let context = builder.builder_mut();
+            if verification.certificate {
+                context.set_verify(SSL_VERIFY_PEER);
+                if let Some(root_cert) = &root_cert {
+                    context.set_ca_file(root_cert).map_err(tls_err)?;
+                }
+            } else {
+                context.set_verify(SSL_VERIFY_NONE);
+            }
+            if let Some(cert) = option(&options.ssl_cert, "sslcert") {
+                context
+                    .set_certificate_file(&cert, X509_FILETYPE_PEM)
+                    .map_err(tls_err)?;
+            }
+            if let Some(key) = option(&options.ssl_key, "sslkey") {
+                context
+                    .set_private_key_file(&key, X509_FILETYPE_PEM)
+                    .map_err(tls_err)?;
+            }
+        }
+        let mut handshake = OpenSsl::from(builder.build());
+        handshake.danger_disable_hostname_verification(!verification.hostname);
+        Ok(Self {
+            mode,
+            handshake: Some((handshake, verification.required)),
+        })
+    }
+
+    pub fn tls_mode(&self) -> TlsMode<'_> {
+        match &self.handshake {
+            None => TlsMode::None,
+            Some((handshake, true)) => TlsMode::Require(handshake),
+            Some((handshake, false)) => TlsMode::Prefer(handshake),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_sslmodes() {
+        for mode in &["disable", "prefer", "require", "verify-ca", "verify-full"] {
+            assert_eq!(mode.parse::<SslMode>().unwrap().to_string(), *mode);
+        }
+        assert!("allow-all".parse::<SslMode>().is_err());
+    }
+
+    #[test]
+    fn sslmode_verifications() {
+        let checks = |mode, has_root_cert| {
+            verification(mode, has_root_cert).map(|v| (v.required, v.certificate, v.hostname))
+        };
+        assert_eq!(checks(SslMode::Disable, true), None);
+        assert_eq!(checks(SslMode::Prefer, false), Some((false, false, false)));
+        assert_eq!(checks(SslMode::Require, false), Some((true, false, false)));
+        assert_eq!(checks(SslMode::Require, true), Some((true, true, false)));
+        assert_eq!(checks(SslMode::VerifyCa, false), Some((true, true, false)));
+        assert_eq!(checks(SslMode::VerifyFull, false), Some((true, true, true)));
+    }
+
+    fn kind(tls_mode: TlsMode) -> &'static str {
+        match tls_mode {
+            TlsMode::None => "none",
+            TlsMode::Prefer(_) => "prefer",
+            TlsMode::Require(_) => "require",
+        }
+    }
+
+    #[test]
+    fn sslmode_precedence() {
+        let options = |sslmode: Option<SslMode>| TlsOptions {
+            sslmode,
+            ssl_root_cert: None,
+            ssl_cert: None,
+            ssl_key: None,
+        };
+        let from_url: HashMap<_, _> = vec![("sslmode".to_owned(), "require".to_owned())]
+            .into_iter()
+            .collect();
+
+        let tls = Tls::new(&options(None), &HashMap::new()).unwrap();
+        assert_eq!(tls.mode, SslMode::Disable);
+        assert_eq!(kind(tls.tls_mode()), "none");
+
+        let tls = Tls::new(&options(None), &from_url).unwrap();
+        assert_eq!(tls.mode, SslMode::Require);
+        assert_eq!(kind(tls.tls_mode()), "require");
+
+        let tls = Tls::new(&options(Some(SslMode::Prefer)), &from_url).unwrap();
+        assert_eq!(tls.mode, SslMode::Prefer);
+        assert_eq!(kind(tls.tls_mode()), "prefer");
+
+        let invalid: HashMap<_, _> = vec![("sslmode".to_owned(), "maybe".to_owned())]
+            .into_iter()
+            .collect();
+        assert!(Tls::new(&options(None), &invalid).is_err());
+    }
+
+    #[test]
+    fn missing_root_cert() {
+        let options = TlsOptions {
+            sslmode: Some(SslMode::VerifyFull),
+            ssl_root_cert: Some("/nonexistent/root.crt".to_owned()),
+            ssl_cert: None,
+            ssl_key: None,
+        };
+        let err = Tls::new(&options, &HashMap::new()).err().unwrap();
+        assert!(err.to_string().contains("sslmode verify-full"));
+    }
+}
@@ -0,0 +1,42 @@
+use crate::AdministrativeRegion;
+use std::fmt;
+
+/// index of a zone in the cosmogony file, usable as a HashMap key as
+/// cosmogony::ZoneIndex implements neither Hash nor Display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LocalZoneIndex(pub u64);
+
+impl From<cosmogony::ZoneIndex> for LocalZoneIndex {
+    fn from(index: cosmogony::ZoneIndex) -> Self {
+        LocalZoneIndex(index.index as u64)
+    }
+}
+
+impl fmt::Display for LocalZoneIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl AdministrativeRegion {
+    /// index of the zone of the city, its id being this index
+    pub fn zone_index(&self) -> LocalZoneIndex {
+        LocalZoneIndex(self.id as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn zone_index_key() {
+        let index = LocalZoneIndex::from(cosmogony::ZoneIndex { index: 42 });
+        assert_eq!(index.to_string(), "42");
+
+        let mut names = HashMap::new();
+        names.insert(index, "Paris");
+        assert_eq!(names.get(&LocalZoneIndex(42)), Some(&"Paris"));
+    }
+}
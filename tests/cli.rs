@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+/// file of the temporary directory, unique to the test process
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "cosmogony2cities_cli_{}_{}",
+        std::process::id(),
+        name
+    ))
+}
+
+#[test]
+fn ndjson_output_with_log_file() {
+    let input = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/cities.jsonl");
+    let log_file = temp_path("import.log");
+    let output = temp_path("cities.jsonl");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cosmogony2cities"))
+        .args(&["--input", input, "--progress-interval", "0", "--log-file"])
+        .arg(&log_file)
+        .arg("--output-ndjson")
+        .arg(&output)
+        .env("RUST_LOG", "info")
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // the state district is not a city
+    let cities = std::fs::read_to_string(&output).unwrap();
+    assert_eq!(cities.lines().count(), 2);
+    let logs = std::fs::read_to_string(&log_file).unwrap();
+    assert!(
+        logs.contains("read 3 zones, converted 2 cities, inserted 0 rows"),
+        "no summary in the logs:\n{}",
+        logs
+    );
+
+    std::fs::remove_file(&log_file).unwrap();
+    std::fs::remove_file(&output).unwrap();
+}